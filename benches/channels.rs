@@ -17,7 +17,21 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, criterion_benchmark);
+pub fn broadcast_benchmark(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    for subscribers in [1, 16, 128] {
+        c.bench_with_input(
+            BenchmarkId::new("unsync_broadcast", subscribers),
+            &subscribers,
+            |b, subscribers| b.iter(|| rt.block_on(test_unsync_broadcast(*subscribers, 256))),
+        );
+    }
+}
+
+criterion_group!(benches, criterion_benchmark, broadcast_benchmark);
 criterion_main!(benches);
 
 async fn test_unsync(size: u32) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
@@ -133,3 +147,45 @@ async fn test_tokio(size: u32) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
 
     Ok(actual)
 }
+
+async fn test_unsync_broadcast(subscribers: usize, size: usize) -> usize {
+    use unsync::broadcast;
+
+    let local = task::LocalSet::new();
+
+    local
+        .run_until(async move {
+            let mut tx = broadcast::channel::<Vec<u8>>(16);
+            let mut receivers = Vec::new();
+
+            for _ in 0..subscribers {
+                let mut rx = tx.subscribe();
+
+                receivers.push(task::spawn_local(async move {
+                    let mut total = 0;
+
+                    while let Some(value) = rx.recv().await {
+                        total += value.len();
+                    }
+
+                    total
+                }));
+            }
+
+            for n in 0..size {
+                tx.send(vec![n as u8; 1024]).await;
+            }
+
+            drop(tx);
+
+            let mut total = 0;
+
+            for r in receivers {
+                total += r.await.unwrap();
+            }
+
+            assert_eq!(total, subscribers * size * 1024);
+            total
+        })
+        .await
+}
@@ -0,0 +1,355 @@
+//! Unsynchronized rendezvous primitives, for waiting until a group of tasks
+//! have all reached the same point.
+//!
+//! * [Barrier] waits for a fixed number of tasks to arrive, and can be reused
+//!   for multiple phases.
+//! * [WaitGroup] waits for a dynamic number of handles to be dropped.
+
+use alloc::rc::Rc;
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::wait_list::WaitList;
+
+/// A barrier which makes tasks wait until a given number of them have
+/// arrived.
+///
+/// Once the last task arrives all waiting tasks are released at once and the
+/// barrier resets, so that it can be used again for the next phase.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use tokio::task;
+/// use unsync::barrier::Barrier;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+/// let barrier = Rc::new(Barrier::new(4));
+///
+/// let local = task::LocalSet::new();
+///
+/// let leaders = local.run_until(async {
+///     let mut tasks = Vec::new();
+///
+///     for _ in 0..4 {
+///         let barrier = barrier.clone();
+///         tasks.push(task::spawn_local(async move { barrier.wait().await.is_leader() }));
+///     }
+///
+///     let mut leaders = 0;
+///
+///     for t in tasks {
+///         leaders += usize::from(t.await?);
+///     }
+///
+///     Ok::<_, task::JoinError>(leaders)
+/// }).await?;
+///
+/// assert_eq!(leaders, 1);
+/// # Ok(()) }
+/// ```
+pub struct Barrier {
+    /// The number of tasks which need to arrive to release the barrier.
+    n: usize,
+    /// The number of tasks which have arrived in the current generation.
+    arrived: Cell<usize>,
+    /// Incremented every time the barrier is released.
+    generation: Cell<u64>,
+    /// Tasks waiting for the barrier to be released.
+    waiters: RefCell<WaitList<()>>,
+}
+
+impl Barrier {
+    /// Construct a new barrier which releases once `n` tasks are waiting on
+    /// it.
+    ///
+    /// A barrier constructed with `n` set to `0` behaves like one where `n` is
+    /// `1`, meaning that every call to [Barrier::wait] completes immediately.
+    pub fn new(n: usize) -> Self {
+        Self {
+            n: n.max(1),
+            arrived: Cell::new(0),
+            generation: Cell::new(0),
+            waiters: RefCell::new(WaitList::new()),
+        }
+    }
+
+    /// Wait until all tasks have reached this point.
+    ///
+    /// A task counts as having arrived once the returned future is first
+    /// polled. If the future is dropped before the barrier is released, the
+    /// task no longer counts towards it.
+    ///
+    /// Exactly one task in every generation is marked as the leader through
+    /// [BarrierWaitResult::is_leader].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::barrier::Barrier;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let barrier = Barrier::new(2);
+    ///
+    /// let (a, b) = tokio::join!(barrier.wait(), barrier.wait());
+    /// assert!(!a.is_leader());
+    /// assert!(b.is_leader());
+    ///
+    /// // The barrier can be reused.
+    /// let (a, b) = tokio::join!(barrier.wait(), barrier.wait());
+    /// assert!(a.is_leader() ^ b.is_leader());
+    /// # }
+    /// ```
+    pub async fn wait(&self) -> BarrierWaitResult {
+        BarrierWait {
+            barrier: self,
+            generation: None,
+            key: None,
+        }
+        .await
+    }
+}
+
+/// The result of waiting on a [Barrier].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Test if this task was the one which released the barrier.
+    ///
+    /// Exactly one task in every generation of the barrier is the leader.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+/// Future associated with waiting through [Barrier::wait].
+struct BarrierWait<'a> {
+    barrier: &'a Barrier,
+    /// The generation of the barrier we arrived in, if we have arrived.
+    generation: Option<u64>,
+    /// Our key in the wait list, if we are waiting.
+    key: Option<usize>,
+}
+
+impl Future for BarrierWait<'_> {
+    type Output = BarrierWaitResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let barrier = this.barrier;
+        let mut waiters = barrier.waiters.borrow_mut();
+
+        let generation = match this.generation {
+            Some(generation) => generation,
+            None => {
+                let arrived = barrier.arrived.get() + 1;
+
+                if arrived == barrier.n {
+                    barrier.arrived.set(0);
+                    barrier
+                        .generation
+                        .set(barrier.generation.get().wrapping_add(1));
+                    waiters.wake_all();
+                    return Poll::Ready(BarrierWaitResult(true));
+                }
+
+                barrier.arrived.set(arrived);
+                let generation = barrier.generation.get();
+                this.generation = Some(generation);
+                generation
+            }
+        };
+
+        if barrier.generation.get() != generation {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            this.generation = None;
+            return Poll::Ready(BarrierWaitResult(false));
+        }
+
+        match this.key {
+            Some(key) => waiters.register(key, cx.waker()),
+            None => this.key = Some(waiters.push_back((), cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for BarrierWait<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.barrier.waiters.borrow_mut().remove(key);
+        }
+
+        // We no longer count towards the barrier if it hasn't been released
+        // yet.
+        if self.generation == Some(self.barrier.generation.get()) {
+            self.barrier.arrived.set(self.barrier.arrived.get() - 1);
+        }
+    }
+}
+
+/// Interior shared state of a [WaitGroup].
+struct WaitGroupShared {
+    /// The number of live handles.
+    count: Cell<usize>,
+    /// Tasks waiting for the count to reach zero.
+    waiters: RefCell<WaitList<()>>,
+}
+
+/// A group of handles which can be waited on until all of them have been
+/// dropped.
+///
+/// Cloning a wait group increments its counter and dropping a handle
+/// decrements it. [WaitGroup::wait] completes once the counter reaches zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use tokio::task;
+/// use unsync::barrier::WaitGroup;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let done = Rc::new(Cell::new(0));
+/// let wg = WaitGroup::new();
+///
+/// let local = task::LocalSet::new();
+///
+/// local.run_until(async {
+///     for _ in 0..4 {
+///         let wg = wg.clone();
+///         let done = done.clone();
+///
+///         task::spawn_local(async move {
+///             task::yield_now().await;
+///             done.set(done.get() + 1);
+///             drop(wg);
+///         });
+///     }
+///
+///     wg.wait().await;
+/// }).await;
+///
+/// assert_eq!(done.get(), 4);
+/// # }
+/// ```
+pub struct WaitGroup {
+    inner: Rc<WaitGroupShared>,
+}
+
+impl WaitGroup {
+    /// Construct a new wait group with a single handle.
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(WaitGroupShared {
+                count: Cell::new(1),
+                waiters: RefCell::new(WaitList::new()),
+            }),
+        }
+    }
+
+    /// Get the number of live handles to the wait group.
+    pub fn count(&self) -> usize {
+        self.inner.count.get()
+    }
+
+    /// Drop this handle and wait until all other handles have been dropped.
+    ///
+    /// Any number of tasks may wait on the same wait group at the same time
+    /// through handles of their own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::barrier::WaitGroup;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let wg = WaitGroup::new();
+    /// let other = wg.clone();
+    /// assert_eq!(wg.count(), 2);
+    ///
+    /// let ((), ()) = tokio::join!(wg.wait(), async move { drop(other) });
+    /// # }
+    /// ```
+    pub async fn wait(self) {
+        let inner = self.inner.clone();
+        drop(self);
+
+        WaitGroupWait { inner, key: None }.await
+    }
+}
+
+impl Default for WaitGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for WaitGroup {
+    fn clone(&self) -> Self {
+        self.inner.count.set(self.inner.count.get() + 1);
+
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Drop for WaitGroup {
+    fn drop(&mut self) {
+        let count = self.inner.count.get() - 1;
+        self.inner.count.set(count);
+
+        if count == 0 {
+            self.inner.waiters.borrow_mut().wake_all();
+        }
+    }
+}
+
+/// Future associated with waiting through [WaitGroup::wait].
+struct WaitGroupWait {
+    inner: Rc<WaitGroupShared>,
+    /// Our key in the wait list, if we are waiting.
+    key: Option<usize>,
+}
+
+impl Future for WaitGroupWait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let mut waiters = this.inner.waiters.borrow_mut();
+
+        if this.inner.count.get() == 0 {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            return Poll::Ready(());
+        }
+
+        match this.key {
+            Some(key) => waiters.register(key, cx.waker()),
+            None => this.key = Some(waiters.push_back((), cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for WaitGroupWait {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.inner.waiters.borrow_mut().remove(key);
+        }
+    }
+}
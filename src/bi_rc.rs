@@ -7,11 +7,12 @@
 //! * Be able to flag when any of the two references of [BiRc] have been
 //!   dropped.
 //!
-//! Now it's true that this API is roughly available through [Rc][std::rc::Rc],
+//! Now it's true that this API is roughly available through [Rc][alloc::rc::Rc],
 //! but it would be more awkward to wrap to use correctly.
 
-use std::cell::UnsafeCell;
-use std::ptr::NonNull;
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::ptr::NonNull;
 
 struct Inner<T> {
     /// The interior value being reference counted.
@@ -48,6 +49,7 @@ impl<T> BiRc<T> {
     ///
     /// Caller must ensure that the reference returned by `get_mut_unchecked` is
     /// only used by one caller at the same time.
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn get_mut_unchecked(&self) -> (&mut T, bool) {
         let inner = &mut (*self.inner.as_ptr());
         (inner.value.get_mut(), inner.count == 2)
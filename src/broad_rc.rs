@@ -8,11 +8,12 @@
 //!   the other ends knows. Allowing them to have an idea when the other end
 //!   "disconnects".
 //!
-//! Now it's true that this API is roughly available through [Rc][std::rc::Rc],
+//! Now it's true that this API is roughly available through [Rc][alloc::rc::Rc],
 //! but it would be more awkward to wrap to use correctly.
 
-use std::cell::UnsafeCell;
-use std::ptr::NonNull;
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::ptr::NonNull;
 
 struct Inner<T> {
     /// The interior value being reference counted.
@@ -57,7 +58,7 @@ impl<T> BroadRc<T> {
     /// Construct a new weak reference.
     pub fn weak(&self) -> BroadWeak<T> {
         unsafe {
-            let mut inner = &mut (*self.inner.as_ptr());
+            let inner = &mut (*self.inner.as_ptr());
             inner.weak += 1;
 
             BroadWeak { inner: self.inner }
@@ -71,10 +72,28 @@ impl<T> BroadRc<T> {
     ///
     /// Caller must ensure that the reference returned by `get_mut_unchecked` is
     /// only used by one caller at the same time.
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn get_mut_unchecked(&self) -> (&mut T, bool) {
         let inner = &mut (*self.inner.as_ptr());
         (inner.value.get_mut(), inner.weak != 0)
     }
+
+    /// Get the number of strong references which are currently alive,
+    /// including this one.
+    pub fn strong_count(&self) -> usize {
+        unsafe { (*self.inner.as_ptr()).strong }
+    }
+}
+
+impl<T> Clone for BroadRc<T> {
+    fn clone(&self) -> Self {
+        unsafe {
+            let inner = &mut (*self.inner.as_ptr());
+            inner.strong += 1;
+
+            BroadRc { inner: self.inner }
+        }
+    }
 }
 
 pub struct BroadWeak<T> {
@@ -89,10 +108,28 @@ impl<T> BroadWeak<T> {
     ///
     /// Caller must ensure that the reference returned by `get_mut_unchecked` is
     /// only used by one caller at the same time.
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn get_mut_unchecked(&self) -> (&mut T, bool) {
         let inner = &mut (*self.inner.as_ptr());
         (inner.value.get_mut(), inner.strong != 0)
     }
+
+    /// Get the number of weak references which are currently alive, including
+    /// this one.
+    pub fn weak_count(&self) -> usize {
+        unsafe { (*self.inner.as_ptr()).weak }
+    }
+}
+
+impl<T> Clone for BroadWeak<T> {
+    fn clone(&self) -> Self {
+        unsafe {
+            let inner = &mut (*self.inner.as_ptr());
+            inner.weak += 1;
+
+            BroadWeak { inner: self.inner }
+        }
+    }
 }
 
 impl<T> Drop for BroadRc<T> {
@@ -1,28 +1,49 @@
-//! An unsynchronized broadcast channel with guaranteed delivery.
+//! An unsynchronized broadcast channel.
+//!
+//! Channels created through [channel] and [unbounded] guarantee delivery to
+//! every subscriber, which means that the [Sender] waits for slow receivers.
+//!
+//! Channels created through [lossy] never make the [LossySender] wait.
+//! Instead a receiver which falls too far behind skips ahead to the oldest
+//! retained message and is told how many messages it missed through
+//! [RecvError::Lagged].
+//!
+//! Channels created through [shared] deliver every message as an [Rc], so
+//! payloads which are large or not [Clone] can be broadcast without cloning
+//! them. The value is dropped once the last subscriber is done with it.
+//!
+//! Every message is stored once in a ring buffer which is shared by all
+//! subscribers, and is only cloned as it's being received. When the last
+//! subscriber to receive a message does so while it's the oldest message in
+//! the ring, it takes the message without cloning it.
 //!
 //! This allocates storage internally to maintain shared state between the
-//! [Sender] and [Receiver]s.
-
-use std::collections::VecDeque;
-use std::error::Error;
-use std::fmt;
-use std::fmt::Display;
-use std::fmt::Formatter;
-use std::future::Future;
-use std::num::NonZeroUsize;
-use std::pin::Pin;
-use std::task::{Context, Poll, Waker};
+//! senders and receivers.
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Debug;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::future::Future;
+use core::mem;
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
 
 use crate::broad_rc::{BroadRc, BroadWeak};
 
 const DEFAULT_CAPACITY: usize = 16;
 
-/// Error raised when trying to [Sender::try_send] but the subscribers either do
-/// not have the necessary capacity or there are no subscribers.
+/// Error raised when trying to [Sender::try_send] but some subscriber doesn't
+/// have the capacity to receive the value, in which case it isn't delivered
+/// to anyone.
 ///
-/// The error includes the number of receivers that the value was delivered to.
+/// Use [Sender::try_send_all] to get the value back instead.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct UnderCapacity(pub usize);
+pub struct UnderCapacity;
 
 impl Display for UnderCapacity {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -30,92 +51,479 @@ impl Display for UnderCapacity {
     }
 }
 
-impl Error for UnderCapacity {}
+#[cfg(feature = "std")]
+impl std::error::Error for UnderCapacity {}
 
-struct ReceiverState<T> {
-    /// Last message id received.
-    id: u64,
-    /// Waker to wake once receiving is available.
-    waker: Option<Waker>,
-    /// Test if the interior value is set.
-    buf: VecDeque<T>,
-    /// If the received is unbounded.
-    unbounded: bool,
+/// Error raised when trying to [Sender::try_send_all], which hands back the
+/// value that couldn't be sent.
+///
+/// # Examples
+///
+/// ```
+/// use unsync::broadcast::{self, TrySendError};
+///
+/// let mut tx = broadcast::channel::<u32>(1);
+/// assert_eq!(tx.try_send_all(1), Err(TrySendError::Closed(1)));
+///
+/// let _sub = tx.subscribe();
+/// assert_eq!(tx.try_send_all(2), Ok(1));
+/// assert_eq!(tx.try_send_all(3), Err(TrySendError::Full(3)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrySendError<T> {
+    /// Some subscriber doesn't have the capacity to receive the value.
+    Full(T),
+    /// There are no subscribers.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Get the value which couldn't be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) => value,
+            TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> Display for TrySendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => write!(f, "subscribers are under capacity"),
+            TrySendError::Closed(..) => write!(f, "no subscribers"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for TrySendError<T> where T: fmt::Debug {}
+
+/// Error raised when trying to receive a message through a [Receiver] without
+/// blocking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TryRecvError {
+    /// There are no messages waiting to be received.
+    Empty,
+    /// The [Sender] has been dropped and all buffered messages have been
+    /// received.
+    Closed,
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "channel empty"),
+            TryRecvError::Closed => write!(f, "channel disconnected"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryRecvError {}
+
+/// Error raised when receiving through a [LossyReceiver].
+///
+/// # Examples
+///
+/// ```
+/// use unsync::broadcast::{self, RecvError};
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let mut tx = broadcast::lossy::<u32>(1);
+/// let mut rx = tx.subscribe();
+///
+/// tx.send(1);
+/// tx.send(2);
+/// drop(tx);
+///
+/// assert_eq!(rx.recv().await, Err(RecvError::Lagged(1)));
+/// assert_eq!(rx.recv().await, Ok(2));
+/// assert_eq!(rx.recv().await, Err(RecvError::Closed));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RecvError {
+    /// The sender has been dropped and all retained messages have been
+    /// received.
+    Closed,
+    /// The receiver fell behind and skipped ahead to the oldest retained
+    /// message. Contains the number of messages which were skipped.
+    Lagged(u64),
 }
 
-impl<T> ReceiverState<T> {
-    /// Test if the current receiver is at capacity.
-    fn at_capacity(&self) -> bool {
-        !self.unbounded && self.buf.capacity() == self.buf.len()
+impl Display for RecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Closed => write!(f, "channel disconnected"),
+            RecvError::Lagged(n) => write!(f, "receiver lagged behind by {} messages", n),
+        }
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for RecvError {}
+
+/// A message stored in the channel.
+struct Slot<T> {
+    /// The stored value.
+    value: T,
+    /// The number of receivers which have yet to receive the value.
+    remaining: usize,
+}
+
+/// The read position of a single receiver.
+struct Cursor {
+    /// Sequence number of the next message to receive.
+    next: u64,
+    /// Sequence number at which the receiver stops receiving, if it has been
+    /// closed.
+    end: Option<u64>,
+    /// Waker to wake once receiving is available.
+    waker: Option<Waker>,
+}
+
 /// Interior shared state.
+///
+/// Every message is stored once in a ring of slots, and every receiver keeps a
+/// cursor into it. Each slot keeps track of how many receivers have yet to
+/// receive it, so that it can be released as soon as the last one has.
+///
+/// Since receivers receive slots in order and only count towards slots sent
+/// after they subscribed, a slot which has been received by everyone is always
+/// found at the front of the ring.
 struct Shared<T> {
-    /// The current message identifier.
-    id: u64,
+    /// Sequence number of the first slot in the ring.
+    head: u64,
+    /// Retained messages, oldest first.
+    slots: VecDeque<Slot<T>>,
+    /// The maximum number of retained messages, or `None` if unbounded.
+    capacity: Option<NonZeroUsize>,
+    /// The number of slots held by outstanding permits.
+    reserved: usize,
     /// Waker to wake once sending is available.
     sender: Option<Waker>,
+    /// Waker to wake once there are no open receivers left.
+    closed: Option<Waker>,
     /// Collection of receivers.
-    receivers: slab::Slab<ReceiverState<T>>,
-    /// Per-subscriber capacity to use.
-    capacity: Option<NonZeroUsize>,
+    receivers: slab::Slab<Cursor>,
+    /// Set once the sender has been closed as a sink.
+    sender_closed: bool,
+    /// The number of receivers which haven't been closed.
+    open: usize,
 }
 
-/// Sender end of the channel created through [channel].
-pub struct Sender<T>
-where
-    T: Clone,
-{
-    inner: BroadRc<Shared<T>>,
-}
+impl<T> Shared<T> {
+    /// Construct new shared state with the given capacity.
+    fn new(capacity: Option<NonZeroUsize>) -> Self {
+        let slots = match capacity {
+            Some(capacity) => VecDeque::with_capacity(capacity.get()),
+            None => VecDeque::with_capacity(DEFAULT_CAPACITY),
+        };
+
+        Self {
+            head: 0,
+            slots,
+            capacity,
+            reserved: 0,
+            sender: None,
+            closed: None,
+            receivers: slab::Slab::new(),
+            sender_closed: false,
+            open: 0,
+        }
+    }
 
-impl<T> Sender<T>
-where
-    T: Clone,
-{
-    /// Construct a new receiver and return its index in the slab of stored
-    /// receivers.
-    fn new_receiver(&mut self) -> usize {
-        // Safety: Since this structure is single-threaded there is now way to
-        // hold an inner reference at multiple locations.
-        unsafe {
-            let (inner, _) = self.inner.get_mut_unchecked();
+    /// Sequence number of the next message to be sent.
+    fn tail(&self) -> u64 {
+        self.head + self.slots.len() as u64
+    }
 
-            let (capacity, unbounded) = match inner.capacity {
-                Some(capacity) => (capacity.get(), false),
-                None => (DEFAULT_CAPACITY, true),
-            };
+    /// Test if the ring is at capacity.
+    fn is_full(&self) -> bool {
+        !self.has_capacity(1)
+    }
 
-            inner.receivers.insert(ReceiverState {
-                id: inner.id,
-                waker: None,
-                buf: VecDeque::with_capacity(capacity),
-                unbounded,
-            })
+    /// Test if there is capacity to hold `n` additional messages.
+    fn has_capacity(&self, n: usize) -> bool {
+        match self.capacity {
+            Some(capacity) => self.slots.len() + self.reserved + n <= capacity.get(),
+            None => true,
         }
     }
 
-    /// Bump the current message ID.
-    fn bump_message_id(&mut self) {
-        // Increase the ID of messages to send.
-        unsafe {
-            let (inner, _) = self.inner.get_mut_unchecked();
+    /// Add a new receiver which receives every message sent from now on,
+    /// returning its index in the slab of receivers.
+    fn subscribe(&mut self) -> usize {
+        let next = self.tail();
+        self.open += 1;
+
+        self.receivers.insert(Cursor {
+            next,
+            end: None,
+            waker: None,
+        })
+    }
+
+    /// Close the receiver with the given index, so that it only receives the
+    /// messages which have already been sent.
+    fn close(&mut self, index: usize) {
+        let tail = self.tail();
+
+        if let Some(receiver) = self.receivers.get_mut(index) {
+            if receiver.end.is_none() {
+                receiver.end = Some(tail);
+                self.closed_one();
+            }
+        }
+    }
+
+    /// Remove the receiver with the given index, releasing its interest in
+    /// any messages it hasn't received yet.
+    fn unsubscribe(&mut self, index: usize) {
+        if self.receivers.contains(index) {
+            self.skip_all(index);
+
+            if self.receivers.remove(index).end.is_none() {
+                self.closed_one();
+            }
+        }
+    }
+
+    /// Account for one less open receiver.
+    fn closed_one(&mut self) {
+        self.open -= 1;
+
+        if self.open == 0 {
+            if let Some(waker) = self.closed.take() {
+                waker.wake();
+            }
+
+            // A sender waiting for capacity now sends to nobody.
+            self.wake_sender();
+        }
+    }
+
+    /// Test if the receiver with the given index will never receive another
+    /// message once it has received the ones it's currently able to.
+    fn is_receiver_closed(&self, index: usize) -> bool {
+        self.sender_closed
+            || !matches!(self.receivers.get(index), Some(receiver) if receiver.end.is_none())
+    }
+
+    /// Store a message for every current receiver and wake them up.
+    ///
+    /// Returns the number of receivers the message was stored for.
+    fn push(&mut self, value: T) -> usize {
+        let remaining = self.store(value);
+
+        if remaining > 0 {
+            self.wake_open_receivers();
+        }
+
+        remaining
+    }
+
+    /// Store a message for every current receiver without waking them up.
+    ///
+    /// Returns the number of receivers the message was stored for.
+    fn store(&mut self, value: T) -> usize {
+        let remaining = self.open;
+
+        if remaining > 0 {
+            self.slots.push_back(Slot { value, remaining });
+        }
+
+        remaining
+    }
+
+    /// Wake every receiver which hasn't been closed.
+    fn wake_open_receivers(&self) {
+        for (_, receiver) in &self.receivers {
+            if receiver.end.is_some() {
+                continue;
+            }
+
+            if let Some(waker) = &receiver.waker {
+                waker.wake_by_ref();
+            }
+        }
+    }
+
+    /// Discard the oldest message, regardless of whether it has been
+    /// received by everyone.
+    fn evict(&mut self) {
+        if self.slots.pop_front().is_some() {
+            self.head += 1;
+        }
+    }
+
+    /// Take the next message for the receiver with the given index.
+    ///
+    /// The caller must ensure that the receiver hasn't fallen behind the
+    /// front of the ring.
+    fn take(&mut self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        let head = self.head;
+        let value = self.take_next(index);
+
+        if self.head != head {
+            self.wake_sender();
+        }
+
+        value
+    }
+
+    /// Take up to `limit` messages for the receiver with the given index,
+    /// appending them to `buf` and waking the sender at most once.
+    ///
+    /// Returns the number of messages taken. The same constraints apply as
+    /// for [Shared::take].
+    fn take_many(&mut self, index: usize, buf: &mut Vec<T>, limit: usize) -> usize
+    where
+        T: Clone,
+    {
+        let head = self.head;
+        let mut n = 0;
+
+        while n < limit {
+            match self.take_next(index) {
+                Some(value) => buf.push(value),
+                None => break,
+            }
+
+            n += 1;
+        }
+
+        if self.head != head {
+            self.wake_sender();
+        }
 
-            inner.id = inner.id.wrapping_add(1);
+        n
+    }
+
+    /// Take the next message for the receiver with the given index without
+    /// waking the sender if that frees up capacity.
+    fn take_next(&mut self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        let tail = self.tail();
+        let receiver = self.receivers.get_mut(index)?;
+
+        if receiver.next >= receiver.end.unwrap_or(tail) {
+            return None;
+        }
+
+        let offset = (receiver.next - self.head) as usize;
+        receiver.next += 1;
+
+        let slot = &mut self.slots[offset];
+        slot.remaining -= 1;
+
+        if slot.remaining == 0 && offset == 0 {
+            let slot = self.slots.pop_front()?;
+            self.head += 1;
+            // Later slots might have been received by everyone else already.
+            self.discard_received();
+            return Some(slot.value);
+        }
+
+        Some(slot.value.clone())
+    }
+
+    /// Skip every message the receiver with the given index hasn't received
+    /// yet.
+    fn skip_all(&mut self, index: usize) {
+        let tail = self.tail();
+
+        if let Some(receiver) = self.receivers.get_mut(index) {
+            let start = receiver.next.max(self.head);
+            let end = receiver.end.unwrap_or(tail);
+            receiver.next = end.max(start);
+
+            let skipped = self
+                .slots
+                .iter_mut()
+                .skip((start - self.head) as usize)
+                .take(end.saturating_sub(start) as usize);
+
+            for slot in skipped {
+                slot.remaining -= 1;
+            }
+
+            self.release();
+        }
+    }
+
+    /// The number of messages the receiver with the given index has yet to
+    /// receive.
+    #[cfg(feature = "futures-core")]
+    fn pending(&self, index: usize) -> usize {
+        match self.receivers.get(index) {
+            Some(receiver) => {
+                let end = receiver.end.unwrap_or(self.tail());
+                end.saturating_sub(receiver.next.max(self.head)) as usize
+            }
+            None => 0,
+        }
+    }
+
+    /// Release slots at the front of the ring which have been received by
+    /// everyone.
+    fn release(&mut self) {
+        let head = self.head;
+        self.discard_received();
+
+        if self.head != head {
+            self.wake_sender();
+        }
+    }
+
+    /// Discard slots at the front of the ring which have been received by
+    /// everyone, without waking the sender.
+    fn discard_received(&mut self) {
+        while matches!(self.slots.front(), Some(slot) if slot.remaining == 0) {
+            self.slots.pop_front();
+            self.head += 1;
+        }
+    }
 
-            // Avoid 0, since that is what receivers are initialized to.
-            if inner.id == 0 {
-                inner.id = 1;
+    /// Wake the sender if it's waiting for capacity.
+    fn wake_sender(&self) {
+        if let Some(waker) = &self.sender {
+            waker.wake_by_ref();
+        }
+    }
+
+    /// Wake every receiver, used when the sender is dropped or closed.
+    fn wake_receivers(&mut self) {
+        for (_, receiver) in &mut self.receivers {
+            if let Some(waker) = receiver.waker.take() {
+                waker.wake();
             }
         }
     }
+}
+
+/// Sender end of the channel created through [channel].
+pub struct Sender<T>
+where
+    T: Clone,
+{
+    inner: BroadRc<Shared<T>>,
+}
 
+impl<T> Sender<T>
+where
+    T: Clone,
+{
     /// Subscribe to the broadcast channel.
     ///
-    /// This will set up a new buffer for the returned [Receiver] which will
-    /// allocate space for the number of elements specified when the channel was
-    /// created with [channel].
+    /// The returned [Receiver] will receive every message sent after this
+    /// call.
     ///
     /// The returned [Receiver] is guaranteed to receive all updates to the
     /// current broadcast channel, even to the extend that sending to other
@@ -154,7 +562,9 @@ where
     /// # }
     /// ```
     pub fn subscribe(&mut self) -> Receiver<T> {
-        let index = self.new_receiver();
+        // Safety: Since this structure is single-threaded there is now way to
+        // hold an inner reference at multiple locations.
+        let index = unsafe { self.inner.get_mut_unchecked().0.subscribe() };
 
         Receiver {
             index,
@@ -162,21 +572,87 @@ where
         }
     }
 
-    /// Get a count on the number of subscribers.
+    /// Get a count on the number of subscribers which haven't been closed.
     pub fn subscribers(&self) -> usize {
         unsafe {
             let (inner, _) = self.inner.get_mut_unchecked();
-            inner.receivers.len()
+            inner.open
         }
     }
 
-    /// Try to send a value to all subscribers in a non-blocking manner.
+    /// Test if there are no subscribers, or all of them have been closed, in
+    /// which case sent values are not delivered to anyone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// let mut tx = broadcast::channel::<u32>(1);
+    /// assert!(tx.is_closed());
+    ///
+    /// let sub = tx.subscribe();
+    /// assert!(!tx.is_closed());
+    ///
+    /// drop(sub);
+    /// assert!(tx.is_closed());
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        self.subscribers() == 0
+    }
+
+    /// Wait until there are no subscribers which haven't been closed.
+    ///
+    /// This resolves immediately if there currently are no subscribers. Note
+    /// that the channel can be opened again by calling [Sender::subscribe].
+    ///
+    /// # Examples
     ///
-    /// This errors with [UnderCapacity] unless all subscribers have the
-    /// capacity to receive the value.
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::channel::<u32>(1);
+    /// let sub1 = tx.subscribe();
+    /// let sub2 = tx.subscribe();
+    ///
+    /// let ((), ()) = tokio::join!(tx.closed(), async move {
+    ///     drop(sub1);
+    ///     tokio::task::yield_now().await;
+    ///     drop(sub2);
+    /// });
+    /// # }
+    /// ```
+    pub async fn closed(&mut self) {
+        Closed { sender: self }.await
+    }
+
+    /// Poll for there to be no subscribers.
+    ///
+    /// If there are subscribers, the current task is registered to be woken up
+    /// once the last one is dropped.
+    pub fn poll_closed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+
+            if inner.open == 0 {
+                inner.closed = None;
+                return Poll::Ready(());
+            }
+
+            if !matches!(&inner.closed, Some(w) if w.will_wake(cx.waker())) {
+                inner.closed = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        }
+    }
+
+    /// Try to send a value to all subscribers in a non-blocking manner.
     ///
-    /// On an error, the subscribers that did have the capacity to receive the
-    /// message will receive it.
+    /// Either delivers the value to every subscriber or, if any lacks
+    /// capacity, to none and errors with [UnderCapacity]. On success this
+    /// returns the number of subscribers the value was sent to.
     ///
     /// # Examples
     ///
@@ -189,50 +665,84 @@ where
     /// let mut sub2 = tx.subscribe();
     ///
     /// assert_eq!(tx.try_send(1), Ok(2));
-    /// assert_eq!(tx.try_send(2), Err(broadcast::UnderCapacity(0)));
+    /// assert_eq!(tx.try_send(2), Err(broadcast::UnderCapacity));
     ///
     /// assert_eq!(sub2.recv().await, Some(1));
-    /// assert_eq!(tx.try_send(3), Err(broadcast::UnderCapacity(1)));
+    /// assert_eq!(tx.try_send(3), Err(broadcast::UnderCapacity));
     ///
+    /// assert_eq!(sub1.recv().await, Some(1));
+    /// assert_eq!(tx.try_send(3), Ok(2));
     /// assert_eq!(sub2.recv().await, Some(3));
     /// # }
     /// ```
     pub fn try_send(&mut self, value: T) -> Result<usize, UnderCapacity> {
-        self.bump_message_id();
+        match self.try_send_all(value) {
+            Ok(n) => Ok(n),
+            Err(TrySendError::Full(..)) => Err(UnderCapacity),
+            Err(TrySendError::Closed(..)) => Ok(0),
+        }
+    }
 
+    /// Try to send a value to all subscribers in a non-blocking manner, handing
+    /// the value back if it couldn't be sent.
+    ///
+    /// The value is either delivered to every subscriber or to none of them.
+    /// On success this returns the number of subscribers the value was sent
+    /// to.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TrySendError::Full] unless all subscribers have the
+    /// capacity to receive the value, or with [TrySendError::Closed] if there
+    /// are no subscribers. Either error holds on to the value that was passed
+    /// in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::channel::<String>(1);
+    /// let mut sub1 = tx.subscribe();
+    /// let mut sub2 = tx.subscribe();
+    ///
+    /// assert_eq!(tx.try_send_all(String::from("first")), Ok(2));
+    /// assert_eq!(sub1.recv().await.as_deref(), Some("first"));
+    ///
+    /// let value = tx.try_send_all(String::from("second")).unwrap_err().into_inner();
+    /// assert_eq!(value, "second");
+    ///
+    /// assert_eq!(sub2.recv().await.as_deref(), Some("first"));
+    /// assert_eq!(tx.try_send_all(value), Ok(2));
+    /// # }
+    /// ```
+    pub fn try_send_all(&mut self, value: T) -> Result<usize, TrySendError<T>> {
         unsafe {
-            let (inner, any_receivers_present) = self.inner.get_mut_unchecked();
-
-            if !any_receivers_present {
-                return Ok(0);
-            }
-
-            let mut delivered = 0;
-
-            for (_, receiver) in &mut inner.receivers {
-                // Receiver buffer is at capacity.
-                if !receiver.at_capacity() {
-                    delivered += 1;
-                    receiver.buf.push_back(value.clone());
+            let (inner, _) = self.inner.get_mut_unchecked();
 
-                    if let Some(waker) = &receiver.waker {
-                        waker.wake_by_ref();
-                    }
-                }
+            if inner.open == 0 || inner.sender_closed {
+                return Err(TrySendError::Closed(value));
             }
 
-            if delivered == inner.receivers.len() {
-                return Ok(delivered);
+            if inner.is_full() {
+                return Err(TrySendError::Full(value));
             }
 
-            Err(UnderCapacity(delivered))
+            Ok(inner.push(value))
         }
     }
 
-    /// Receive a message on the channel.
+    /// Send a message on the channel, waiting until every subscriber has the
+    /// capacity to receive it.
+    ///
+    /// Resolves to the number of subscribers the value was sent to.
+    ///
+    /// # Cancel safety
     ///
-    /// Note that *not driving the returned future to completion* might result
-    /// in some receivers not receiving the most up-to-date value.
+    /// The value is delivered to every subscriber at once when the returned
+    /// future completes. If the future is dropped before that, the value is
+    /// sent to nobody.
     ///
     /// # Examples
     ///
@@ -264,92 +774,862 @@ where
     /// assert_eq!(result, 0);
     /// # }
     /// ```
-    pub async fn send(&mut self, value: T) -> usize {
-        self.bump_message_id();
-
-        Send {
+    pub fn send(&mut self, value: T) -> SendFut<'_, T> {
+        SendFut {
             inner: &self.inner,
-            value,
+            value: Some(value),
         }
-        .await
     }
-}
-
-/// Future produced by [Sender::send].
-struct Send<'a, T> {
-    inner: &'a BroadRc<Shared<T>>,
-    value: T,
-}
 
-impl<'a, T> Future for Send<'a, T>
-where
-    T: Clone,
-{
-    type Output = usize;
+    /// Send every value produced by an iterator on the channel, waiting for
+    /// capacity as needed.
+    ///
+    /// As many values as there is capacity for are stored at once, and
+    /// subscribers are only woken up once per batch rather than once per value.
+    /// Sending stops early if there are no subscribers left to send to.
+    ///
+    /// Resolves to the number of values which were sent.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is not cancel safe. If the returned future is dropped
+    /// before it completes, some of the values might have been sent while the
+    /// rest are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::channel::<u32>(2);
+    /// let mut sub = tx.subscribe();
+    ///
+    /// let (sent, ()) = tokio::join!(tx.send_all(1..=3), async {
+    ///     let mut buf = Vec::new();
+    ///     assert_eq!(sub.recv_many(&mut buf, 8).await, 2);
+    ///     assert_eq!(buf, [1, 2]);
+    /// });
+    ///
+    /// assert_eq!(sent, 3);
+    /// assert_eq!(sub.recv().await, Some(3));
+    ///
+    /// drop(sub);
+    /// assert_eq!(tx.send_all(4..=6).await, 0);
+    /// # }
+    /// ```
+    pub async fn send_all<I>(&mut self, iter: I) -> usize
+    where
+        I: IntoIterator<Item = T>,
+    {
+        SendAll {
+            inner: &self.inner,
+            iter: iter.into_iter(),
+            pending: None,
+            sent: 0,
+        }
+        .await
+    }
+
+    /// Wait until every subscriber has the capacity to receive a message, and
+    /// reserve it.
+    ///
+    /// The reserved slot is held until the returned [Permit] is used to send a
+    /// message or is dropped. This allows for checking that there is room
+    /// before constructing a message which is expensive to build.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::channel::<u32>(1);
+    /// let mut sub1 = tx.subscribe();
+    /// let mut sub2 = tx.subscribe();
+    ///
+    /// let permit = tx.reserve().await;
+    /// assert_eq!(permit.send(42), 2);
+    ///
+    /// assert_eq!(sub1.recv().await, Some(42));
+    /// assert_eq!(sub2.recv().await, Some(42));
+    /// # }
+    /// ```
+    pub async fn reserve(&mut self) -> Permit<'_, T> {
+        Reserve {
+            inner: &self.inner,
+            n: 1,
+        }
+        .await;
+
+        Permit { inner: &self.inner }
+    }
+
+    /// Wait until every subscriber has the capacity to receive `n` messages,
+    /// and reserve all of them at once.
+    ///
+    /// The returned [PermitIterator] yields one [Permit] for every reserved
+    /// slot. Slots which haven't been used are released when it's dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the capacity of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::channel::<u32>(3);
+    /// let mut sub = tx.subscribe();
+    ///
+    /// let permits = tx.reserve_many(3).await;
+    /// assert_eq!(permits.len(), 3);
+    ///
+    /// for (permit, value) in permits.zip([1, 2]) {
+    ///     assert_eq!(permit.send(value), 1);
+    /// }
+    ///
+    /// // The unused slot was released.
+    /// assert_eq!(tx.try_send(3), Ok(1));
+    ///
+    /// assert_eq!(sub.recv().await, Some(1));
+    /// assert_eq!(sub.recv().await, Some(2));
+    /// assert_eq!(sub.recv().await, Some(3));
+    /// # }
+    /// ```
+    pub async fn reserve_many(&mut self, n: usize) -> PermitIterator<'_, T> {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            assert!(
+                !matches!(inner.capacity, Some(capacity) if n > capacity.get()),
+                "cannot reserve more than the capacity of the channel"
+            );
+        }
+
+        Reserve {
+            inner: &self.inner,
+            n,
+        }
+        .await;
+
+        PermitIterator {
+            inner: &self.inner,
+            n,
+        }
+    }
+}
+
+/// Future associated with waiting through [Sender::closed].
+struct Closed<'a, T>
+where
+    T: Clone,
+{
+    sender: &'a mut Sender<T>,
+}
+
+impl<T> Future for Closed<'_, T>
+where
+    T: Clone,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::into_inner(self).sender.poll_closed(cx)
+    }
+}
+
+/// Future associated with sending through [Sender::send_all].
+struct SendAll<'a, T, I> {
+    inner: &'a BroadRc<Shared<T>>,
+    iter: I,
+    /// A value taken from the iterator which there was no capacity for.
+    pending: Option<T>,
+    /// The number of values sent so far.
+    sent: usize,
+}
+
+// Neither the iterator nor the pending value are ever pinned.
+impl<T, I> Unpin for SendAll<'_, T, I> {}
+
+impl<T, I> Future for SendAll<'_, T, I>
+where
+    I: Iterator<Item = T>,
+{
+    type Output = usize;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+
+        unsafe {
+            let (inner, _) = this.inner.get_mut_unchecked();
+            let mut stored = false;
+
+            let result = loop {
+                if inner.open == 0 {
+                    this.pending = None;
+                    break Poll::Ready(this.sent);
+                }
+
+                if inner.is_full() {
+                    if this.pending.is_none() {
+                        this.pending = this.iter.next();
+
+                        if this.pending.is_none() {
+                            break Poll::Ready(this.sent);
+                        }
+                    }
+
+                    if !matches!(&inner.sender, Some(w) if w.will_wake(cx.waker())) {
+                        inner.sender = Some(cx.waker().clone());
+                    }
+
+                    break Poll::Pending;
+                }
+
+                let value = match this.pending.take().or_else(|| this.iter.next()) {
+                    Some(value) => value,
+                    None => break Poll::Ready(this.sent),
+                };
+
+                inner.store(value);
+                this.sent += 1;
+                stored = true;
+            };
+
+            if stored {
+                inner.wake_open_receivers();
+            }
+
+            result
+        }
+    }
+}
+
+/// Future associated with reserving capacity through [Sender::reserve] and
+/// [Sender::reserve_many].
+struct Reserve<'a, T> {
+    inner: &'a BroadRc<Shared<T>>,
+    /// The number of slots to reserve.
+    n: usize,
+}
+
+impl<T> Future for Reserve<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+
+            if !inner.has_capacity(self.n) {
+                if !matches!(&inner.sender, Some(w) if w.will_wake(cx.waker())) {
+                    inner.sender = Some(cx.waker().clone());
+                }
+
+                return Poll::Pending;
+            }
+
+            inner.reserved += self.n;
+            Poll::Ready(())
+        }
+    }
+}
+
+/// A slot reserved in every subscriber's view of the channel through
+/// [Sender::reserve] or [Sender::reserve_many].
+///
+/// The slot is released when this is dropped without being used.
+pub struct Permit<'a, T> {
+    inner: &'a BroadRc<Shared<T>>,
+}
+
+impl<T> Permit<'_, T> {
+    /// Send a message to all subscribers using the reserved slot.
+    ///
+    /// This never waits, since capacity has already been reserved. Returns the
+    /// number of subscribers the value was sent to.
+    pub fn send(self, value: T) -> usize {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.push(value)
+        }
+    }
+}
+
+impl<T> Debug for Permit<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Permit").finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Permit<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.inner.get_mut_unchecked().0.reserved -= 1;
+        }
+    }
+}
+
+/// An iterator over the slots reserved through [Sender::reserve_many].
+///
+/// Slots which haven't been yielded are released when this is dropped.
+pub struct PermitIterator<'a, T> {
+    inner: &'a BroadRc<Shared<T>>,
+    n: usize,
+}
+
+impl<'a, T> Iterator for PermitIterator<'a, T> {
+    type Item = Permit<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n == 0 {
+            return None;
+        }
+
+        self.n -= 1;
+        Some(Permit { inner: self.inner })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.n, Some(self.n))
+    }
+}
+
+impl<T> ExactSizeIterator for PermitIterator<'_, T> {}
+
+impl<T> Debug for PermitIterator<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PermitIterator")
+            .field("n", &self.n)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for PermitIterator<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.inner.get_mut_unchecked().0.reserved -= mem::take(&mut self.n);
+        }
+    }
+}
+
+/// Future produced by [Sender::send].
+///
+/// Resolves to the number of receivers the value was delivered to.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SendFut<'a, T> {
+    inner: &'a BroadRc<Shared<T>>,
+    value: Option<T>,
+}
+
+// The value being sent is never pinned.
+impl<T> Unpin for SendFut<'_, T> {}
+
+impl<'a, T> Future for SendFut<'a, T>
+where
+    T: Clone,
+{
+    type Output = usize;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        unsafe {
+            let this = Pin::into_inner(self);
+
+            let (inner, _) = this.inner.get_mut_unchecked();
+
+            if inner.open == 0 || inner.sender_closed {
+                this.value = None;
+                return Poll::Ready(0);
+            }
+
+            if inner.is_full() {
+                if !matches!(&inner.sender, Some(w) if w.will_wake(cx.waker())) {
+                    inner.sender = Some(cx.waker().clone());
+                }
+
+                return Poll::Pending;
+            }
+
+            let value = this.value.take().expect("future already completed");
+            Poll::Ready(inner.push(value))
+        }
+    }
+}
+
+impl<T> Debug for SendFut<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendFut").finish_non_exhaustive()
+    }
+}
+
+/// Receiver end of the channel created through [channel].
+pub struct Receiver<T> {
+    index: usize,
+    inner: BroadWeak<Shared<T>>,
+}
+
+impl<T> Receiver<T>
+where
+    T: Clone,
+{
+    /// Receive a message on the channel.
+    ///
+    /// Trying to receive a message on a queue that has been closed by dropping
+    /// its [Sender] will result in `None` being returned.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If the returned future is dropped before it
+    /// completes, no message is lost and the next call to `recv` receives it
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut sender = broadcast::channel::<u32>(1);
+    ///
+    /// let mut sub1 = sender.subscribe();
+    /// let mut sub2 = sender.subscribe();
+    ///
+    /// let (result, s1, s2) = tokio::join!(sender.send(42), sub1.recv(), sub2.recv());
+    ///
+    /// assert_eq!(result, 2);
+    /// assert_eq!(s1, Some(42));
+    /// assert_eq!(s2, Some(42));
+    ///
+    /// drop(sender);
+    ///
+    /// let (s1, s2) = tokio::join!(sub1.recv(), sub2.recv());
+    ///
+    /// assert_eq!(s1, None);
+    /// assert_eq!(s2, None);
+    /// # }
+    /// ```
+    pub fn recv(&mut self) -> RecvFut<'_, T> {
+        RecvFut { receiver: self }
+    }
+
+    /// Close the receiver without dropping it.
+    ///
+    /// The receiver stops counting as a subscriber, so it won't receive any
+    /// messages sent from now on. Messages which have already been sent can
+    /// still be received, after which receiving returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::channel::<u32>(4);
+    /// let mut sub1 = tx.subscribe();
+    /// let mut sub2 = tx.subscribe();
+    ///
+    /// assert_eq!(tx.try_send(1), Ok(2));
+    /// sub1.close();
+    /// assert_eq!(tx.subscribers(), 1);
+    /// assert_eq!(tx.try_send(2), Ok(1));
+    ///
+    /// assert_eq!(sub1.recv().await, Some(1));
+    /// assert_eq!(sub1.recv().await, None);
+    ///
+    /// sub2.close();
+    /// assert!(tx.is_closed());
+    /// assert_eq!(tx.try_send_all(3), Err(broadcast::TrySendError::Closed(3)));
+    ///
+    /// assert_eq!(sub2.recv().await, Some(1));
+    /// assert_eq!(sub2.recv().await, Some(2));
+    /// assert_eq!(sub2.recv().await, None);
+    /// # }
+    /// ```
+    pub fn close(&mut self) {
+        unsafe {
+            self.inner.get_mut_unchecked().0.close(self.index);
+        }
+    }
+
+    /// Try to receive a message on the channel without blocking.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryRecvError::Empty] if there are no messages waiting to
+    /// be received, or with [TryRecvError::Closed] if the [Sender] has been
+    /// dropped or the receiver has been closed through [Receiver::close], and
+    /// all buffered messages have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast::{self, TryRecvError};
+    ///
+    /// let mut tx = broadcast::channel::<u32>(2);
+    /// let mut sub = tx.subscribe();
+    /// assert_eq!(sub.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// assert_eq!(tx.try_send(1), Ok(1));
+    /// drop(tx);
+    ///
+    /// assert_eq!(sub.try_recv(), Ok(1));
+    /// assert_eq!(sub.try_recv(), Err(TryRecvError::Closed));
+    /// ```
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        unsafe {
+            let (inner, sender_present) = self.inner.get_mut_unchecked();
+
+            if let Some(value) = inner.take(self.index) {
+                return Ok(value);
+            }
+
+            if !sender_present || inner.is_receiver_closed(self.index) {
+                return Err(TryRecvError::Closed);
+            }
+
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receive up to `limit` messages on the channel, appending them to `buf`.
+    ///
+    /// This waits until at least one message is available, and then receives
+    /// as many as it can at once. Resolves to the number of messages received,
+    /// which is `0` once the channel is closed and all buffered messages have
+    /// been received, or if `limit` is `0`.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If the returned future is dropped before it
+    /// completes, no messages have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::channel::<u32>(4);
+    /// let mut sub = tx.subscribe();
+    ///
+    /// assert_eq!(tx.send_all(1..=3).await, 3);
+    /// drop(tx);
+    ///
+    /// let mut buf = Vec::new();
+    /// assert_eq!(sub.recv_many(&mut buf, 2).await, 2);
+    /// assert_eq!(sub.recv_many(&mut buf, 2).await, 1);
+    /// assert_eq!(sub.recv_many(&mut buf, 2).await, 0);
+    /// assert_eq!(buf, [1, 2, 3]);
+    /// # }
+    /// ```
+    pub async fn recv_many(&mut self, buf: &mut Vec<T>, limit: usize) -> usize {
+        RecvMany {
+            receiver: self,
+            buf,
+            limit,
+        }
+        .await
+    }
+
+    /// Poll for the next message on the channel.
+    ///
+    /// If no message is available the current task is registered to be woken
+    /// up once one is sent, or the [Sender] is dropped. Returns
+    /// `Poll::Ready(None)` once the [Sender] has been dropped and all
+    /// buffered messages have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::future::poll_fn;
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut sender = broadcast::channel::<u32>(1);
+    /// let mut sub = sender.subscribe();
+    ///
+    /// let (result, value) = tokio::join!(sender.send(42), poll_fn(|cx| sub.poll_recv(cx)));
+    /// assert_eq!(result, 1);
+    /// assert_eq!(value, Some(42));
+    ///
+    /// drop(sender);
+    /// assert_eq!(poll_fn(|cx| sub.poll_recv(cx)).await, None);
+    /// # }
+    /// ```
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let closed = match self.try_recv() {
+            Ok(value) => return Poll::Ready(Some(value)),
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Closed) => true,
+        };
+
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+
+            let receiver = match inner.receivers.get_mut(self.index) {
+                Some(receiver) => receiver,
+                None => return Poll::Ready(None),
+            };
+
+            if closed {
+                receiver.waker = None;
+                return Poll::Ready(None);
+            }
+
+            if !matches!(&receiver.waker, Some(w) if w.will_wake(cx.waker())) {
+                receiver.waker = Some(cx.waker().clone())
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+/// Future associated with receiving through [Receiver::recv_many].
+struct RecvMany<'a, T> {
+    receiver: &'a mut Receiver<T>,
+    buf: &'a mut Vec<T>,
+    /// The maximum number of messages to receive.
+    limit: usize,
+}
+
+impl<T> Future for RecvMany<'_, T>
+where
+    T: Clone,
+{
+    type Output = usize;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+
+        if this.limit == 0 {
+            return Poll::Ready(0);
+        }
+
+        let index = this.receiver.index;
+
+        unsafe {
+            let (inner, sender_present) = this.receiver.inner.get_mut_unchecked();
+
+            let n = inner.take_many(index, this.buf, this.limit);
+
+            if n > 0 {
+                return Poll::Ready(n);
+            }
+
+            let closed = !sender_present || inner.is_receiver_closed(index);
+
+            let receiver = match inner.receivers.get_mut(index) {
+                Some(receiver) => receiver,
+                None => return Poll::Ready(0),
+            };
+
+            if closed {
+                receiver.waker = None;
+                return Poll::Ready(0);
+            }
+
+            if !matches!(&receiver.waker, Some(w) if w.will_wake(cx.waker())) {
+                receiver.waker = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+/// Future associated with receiving through [Receiver::recv].
+///
+/// Resolves to `None` once the [Sender] has been dropped and all buffered
+/// messages have been received.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RecvFut<'a, T> {
+    receiver: &'a mut Receiver<T>,
+}
+
+impl<'a, T> Future for RecvFut<'a, T>
+where
+    T: Clone,
+{
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::into_inner(self).receiver.poll_recv(cx)
+    }
+}
+
+impl<T> Debug for RecvFut<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecvFut")
+            .field("index", &self.receiver.index)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> futures_core::Stream for Receiver<T>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::into_inner(self).poll_recv(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        unsafe {
+            let (inner, sender_present) = self.inner.get_mut_unchecked();
+
+            let pending = inner.pending(self.index);
+
+            if sender_present && !inner.is_receiver_closed(self.index) {
+                (pending, None)
+            } else {
+                (pending, Some(pending))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> futures_core::FusedStream for Receiver<T>
+where
+    T: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        unsafe {
+            let (inner, sender_present) = self.inner.get_mut_unchecked();
+
+            (!sender_present || inner.is_receiver_closed(self.index))
+                && inner.pending(self.index) == 0
+        }
+    }
+}
+
+/// The sink is ready once every subscriber has the capacity to receive
+/// another message, and flushing completes immediately. Closing the sink closes
+/// the channel, so subscribers see the end of the stream once they have
+/// received every buffered message. Anything sent after that is delivered to
+/// nobody.
+///
+/// Sending errors with [UnderCapacity] if some subscriber doesn't have the
+/// capacity to receive the value, which means that it was sent without the
+/// sink first being ready.
+#[cfg(feature = "futures-sink")]
+impl<T> futures_sink::Sink<T> for Sender<T>
+where
+    T: Clone,
+{
+    type Error = UnderCapacity;
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         unsafe {
-            let this = Pin::get_unchecked_mut(self);
-
-            let (inner, any_receivers_present) = this.inner.get_mut_unchecked();
+            let (inner, _) = self.inner.get_mut_unchecked();
 
-            if !any_receivers_present {
-                return Poll::Ready(0);
-            }
+            if inner.is_full() {
+                if !matches!(&inner.sender, Some(w) if w.will_wake(cx.waker())) {
+                    inner.sender = Some(cx.waker().clone());
+                }
 
-            if !matches!(&inner.sender, Some(w) if w.will_wake(cx.waker())) {
-                inner.sender = Some(cx.waker().clone());
+                return Poll::Pending;
             }
 
-            loop {
-                let mut any_sent = false;
-                let mut delivered = 0;
-
-                for (_, receiver) in &mut inner.receivers {
-                    if receiver.id == inner.id {
-                        delivered += 1;
-                        continue;
-                    }
+            Poll::Ready(Ok(()))
+        }
+    }
 
-                    // Receiver buffer is at capacity.
-                    if receiver.at_capacity() {
-                        continue;
-                    }
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        Pin::into_inner(self).try_send(item)?;
+        Ok(())
+    }
 
-                    receiver.buf.push_back(this.value.clone());
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
 
-                    if let Some(waker) = &receiver.waker {
-                        waker.wake_by_ref();
-                    }
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.sender_closed = true;
+            inner.wake_receivers();
+        }
 
-                    any_sent = true;
-                }
+        Poll::Ready(Ok(()))
+    }
+}
 
-                if delivered == inner.receivers.len() {
-                    return Poll::Ready(delivered);
-                }
+impl<T> Drop for Sender<T>
+where
+    T: Clone,
+{
+    fn drop(&mut self) {
+        unsafe {
+            self.inner.get_mut_unchecked().0.wake_receivers();
+        }
+    }
+}
 
-                if any_sent {
-                    continue;
-                }
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.unsubscribe(self.index);
 
-                return Poll::Pending;
+            if let Some(waker) = inner.sender.take() {
+                waker.wake();
             }
         }
     }
 }
 
-/// Receiver end of the channel created through [channel].
-pub struct Receiver<T> {
-    index: usize,
-    inner: BroadWeak<Shared<T>>,
+/// Setup a broadcast channel which retains at most `capacity` messages which
+/// haven't been received by every subscriber.
+///
+/// # Panics
+///
+/// Panics if `capacity` is specified as 0.
+pub fn channel<T>(capacity: usize) -> Sender<T>
+where
+    T: Clone,
+{
+    let capacity = NonZeroUsize::new(capacity).expect("capacity cannot be 0");
+
+    let inner = BroadRc::new(Shared::new(Some(capacity)));
+
+    Sender { inner }
 }
 
-impl<T> Receiver<T> {
-    /// Receive a message on the channel.
+/// Setup a broadcast channel which is unbounded.
+///
+/// Sending through this channel will never block.
+pub fn unbounded<T>() -> Sender<T>
+where
+    T: Clone,
+{
+    let inner = BroadRc::new(Shared::new(None));
+
+    Sender { inner }
+}
+
+/// Sender end of the channel created through [lossy].
+pub struct LossySender<T>
+where
+    T: Clone,
+{
+    inner: BroadRc<Shared<T>>,
+}
+
+impl<T> LossySender<T>
+where
+    T: Clone,
+{
+    /// Subscribe to the broadcast channel.
     ///
-    /// Trying to receive a message on a queue that has been closed by dropping
-    /// its [Sender] will result in `None` being returned.
+    /// The returned [LossyReceiver] will receive every message sent after
+    /// this call, unless it falls behind by more than the capacity of the
+    /// channel.
     ///
     /// # Examples
     ///
@@ -357,71 +1637,169 @@ impl<T> Receiver<T> {
     /// use unsync::broadcast;
     ///
     /// # #[tokio::main(flavor = "current_thread")] async fn main() {
-    /// let mut sender = broadcast::channel::<u32>(1);
+    /// let mut tx = broadcast::lossy::<u32>(4);
+    /// tx.send(1);
     ///
-    /// let mut sub1 = sender.subscribe();
-    /// let mut sub2 = sender.subscribe();
+    /// let mut rx = tx.subscribe();
+    /// tx.send(2);
     ///
-    /// let (result, s1, s2) = tokio::join!(sender.send(42), sub1.recv(), sub2.recv());
+    /// assert_eq!(rx.recv().await, Ok(2));
+    /// # }
+    /// ```
+    pub fn subscribe(&mut self) -> LossyReceiver<T> {
+        // Safety: Since this structure is single-threaded there is now way to
+        // hold an inner reference at multiple locations.
+        let index = unsafe { self.inner.get_mut_unchecked().0.subscribe() };
+
+        LossyReceiver {
+            index,
+            inner: self.inner.weak(),
+        }
+    }
+
+    /// Get a count on the number of subscribers which haven't been closed.
+    pub fn subscribers(&self) -> usize {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.open
+        }
+    }
+
+    /// Send a value to all subscribers without waiting.
     ///
-    /// assert_eq!(result, 2);
-    /// assert_eq!(s1, Some(42));
-    /// assert_eq!(s2, Some(42));
+    /// If the channel is at capacity the oldest message is discarded, and
+    /// receivers which haven't received it yet will observe
+    /// [RecvError::Lagged].
     ///
-    /// drop(sender);
+    /// Returns the number of subscribers the value was sent to.
     ///
-    /// let (s1, s2) = tokio::join!(sub1.recv(), sub2.recv());
+    /// # Examples
     ///
-    /// assert_eq!(s1, None);
-    /// assert_eq!(s2, None);
+    /// ```
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::lossy::<u32>(2);
+    /// assert_eq!(tx.send(0), 0);
+    ///
+    /// let mut sub1 = tx.subscribe();
+    /// let mut sub2 = tx.subscribe();
+    ///
+    /// assert_eq!(tx.send(1), 2);
+    /// assert_eq!(sub1.recv().await, Ok(1));
+    /// assert_eq!(sub2.recv().await, Ok(1));
     /// # }
     /// ```
-    pub async fn recv(&mut self) -> Option<T> {
-        Recv { receiver: self }.await
+    pub fn send(&mut self, value: T) -> usize {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+
+            if inner.open == 0 {
+                return 0;
+            }
+
+            if inner.is_full() {
+                inner.evict();
+            }
+
+            inner.push(value)
+        }
     }
 }
 
-/// Future associated with receiving through [Receiver::recv].
-struct Recv<'a, T> {
-    receiver: &'a mut Receiver<T>,
+impl<T> Drop for LossySender<T>
+where
+    T: Clone,
+{
+    fn drop(&mut self) {
+        unsafe {
+            self.inner.get_mut_unchecked().0.wake_receivers();
+        }
+    }
 }
 
-impl<'a, T> Future for Recv<'a, T> {
-    type Output = Option<T>;
+/// Receiver end of the channel created through [lossy].
+pub struct LossyReceiver<T> {
+    index: usize,
+    inner: BroadWeak<Shared<T>>,
+}
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+impl<T> LossyReceiver<T>
+where
+    T: Clone,
+{
+    /// Receive a message on the channel.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [RecvError::Lagged] if the receiver fell behind, after
+    /// which the next call receives the oldest retained message. Errors with
+    /// [RecvError::Closed] once the [LossySender] has been dropped and all
+    /// retained messages have been received.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe, see [Receiver::recv].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::broadcast::{self, RecvError};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut tx = broadcast::lossy::<u32>(2);
+    /// let mut rx = tx.subscribe();
+    ///
+    /// for n in 0..5 {
+    ///     tx.send(n);
+    /// }
+    ///
+    /// assert_eq!(rx.recv().await, Err(RecvError::Lagged(3)));
+    /// assert_eq!(rx.recv().await, Ok(3));
+    /// assert_eq!(rx.recv().await, Ok(4));
+    /// # }
+    /// ```
+    pub fn recv(&mut self) -> LossyRecvFut<'_, T> {
+        LossyRecvFut { receiver: self }
+    }
+
+    /// Poll for the next message on the channel.
+    ///
+    /// If no message is available the current task is registered to be woken
+    /// up once one is sent, or the [LossySender] is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Errors in the same way as [LossyReceiver::recv].
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
         unsafe {
-            let this = Pin::get_unchecked_mut(self);
-            let index = this.receiver.index;
-            let (inner, sender_present) = this.receiver.inner.get_mut_unchecked();
+            let (inner, sender_present) = self.inner.get_mut_unchecked();
+            let head = inner.head;
 
-            let receiver = match inner.receivers.get_mut(index) {
+            let receiver = match inner.receivers.get_mut(self.index) {
                 Some(receiver) => receiver,
-                None => return Poll::Ready(None),
+                None => return Poll::Ready(Err(RecvError::Closed)),
             };
 
-            if let Some(value) = receiver.buf.pop_front() {
-                receiver.id = inner.id;
-
-                // Senders have interest once a buffer has been taken.
-                if let Some(waker) = &inner.sender {
-                    waker.wake_by_ref();
-                }
+            if receiver.next < head {
+                let lagged = head - receiver.next;
+                receiver.next = head;
+                return Poll::Ready(Err(RecvError::Lagged(lagged)));
+            }
 
-                return Poll::Ready(Some(value));
+            if let Some(value) = inner.take(self.index) {
+                return Poll::Ready(Ok(value));
             }
 
+            let receiver = &mut inner.receivers[self.index];
+
             if !sender_present {
                 receiver.waker = None;
-                return Poll::Ready(None);
-            }
-
-            if !matches!(&receiver.waker, Some(w) if !w.will_wake(cx.waker())) {
-                receiver.waker = Some(cx.waker().clone())
+                return Poll::Ready(Err(RecvError::Closed));
             }
 
-            if let Some(waker) = &inner.sender {
-                waker.wake_by_ref();
+            if !matches!(&receiver.waker, Some(w) if w.will_wake(cx.waker())) {
+                receiver.waker = Some(cx.waker().clone());
             }
 
             Poll::Pending
@@ -429,86 +1807,183 @@ impl<'a, T> Future for Recv<'a, T> {
     }
 }
 
-impl<T> Drop for Recv<'_, T> {
+impl<T> Drop for LossyReceiver<T> {
     fn drop(&mut self) {
         unsafe {
-            let index = self.receiver.index;
-            let (inner, _) = self.receiver.inner.get_mut_unchecked();
-
-            if let Some(receiver) = inner.receivers.get_mut(index) {
-                receiver.buf.clear();
-            }
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.unsubscribe(self.index);
         }
     }
 }
 
-impl<T> Drop for Sender<T>
+/// Future associated with receiving through [LossyReceiver::recv].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LossyRecvFut<'a, T> {
+    receiver: &'a mut LossyReceiver<T>,
+}
+
+impl<'a, T> Future for LossyRecvFut<'a, T>
 where
     T: Clone,
 {
-    fn drop(&mut self) {
-        unsafe {
-            let (inner, _) = self.inner.get_mut_unchecked();
+    type Output = Result<T, RecvError>;
 
-            for (_, r) in &mut inner.receivers {
-                if let Some(waker) = r.waker.take() {
-                    waker.wake();
-                }
-            }
-        }
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::into_inner(self).receiver.poll_recv(cx)
     }
 }
 
-impl<T> Drop for Receiver<T> {
-    fn drop(&mut self) {
-        unsafe {
-            let index = self.index;
-            let (inner, _) = self.inner.get_mut_unchecked();
-            let _ = inner.receivers.try_remove(index);
-
-            if let Some(waker) = self.inner.get_mut_unchecked().0.sender.take() {
-                waker.wake();
-            }
-        }
+impl<T> Debug for LossyRecvFut<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LossyRecvFut")
+            .field("index", &self.receiver.index)
+            .finish_non_exhaustive()
     }
 }
 
-/// Setup a broadcast channel with the given per-subscriber capacity.
+/// Setup a lossy broadcast channel which retains at most `capacity`
+/// messages.
+///
+/// Sending on the channel never waits. Receivers which fall behind by more
+/// than `capacity` messages skip ahead and observe [RecvError::Lagged].
 ///
 /// # Panics
 ///
 /// Panics if `capacity` is specified as 0.
-pub fn channel<T>(capacity: usize) -> Sender<T>
+///
+/// # Examples
+///
+/// ```
+/// use unsync::broadcast;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let mut tx = broadcast::lossy::<u32>(16);
+/// let mut sub1 = tx.subscribe();
+/// let mut sub2 = tx.subscribe();
+///
+/// assert_eq!(tx.send(42), 2);
+/// drop(tx);
+///
+/// assert_eq!(sub1.recv().await, Ok(42));
+/// assert_eq!(sub2.recv().await, Ok(42));
+/// assert!(sub1.recv().await.is_err());
+/// # }
+/// ```
+pub fn lossy<T>(capacity: usize) -> LossySender<T>
 where
     T: Clone,
 {
     let capacity = NonZeroUsize::new(capacity).expect("capacity cannot be 0");
+    let inner = BroadRc::new(Shared::new(Some(capacity)));
+    LossySender { inner }
+}
 
-    let inner = BroadRc::new(Shared {
-        id: 0,
-        sender: None,
-        receivers: slab::Slab::new(),
-        capacity: Some(capacity),
-    });
+/// Sender end of the channel created through [shared].
+///
+/// Messages are wrapped in an [Rc] when sent, and every subscriber receives a
+/// reference to the same value.
+pub struct SharedSender<T> {
+    inner: Sender<Rc<T>>,
+}
 
-    Sender { inner }
+impl<T> SharedSender<T> {
+    /// Subscribe to the broadcast channel.
+    ///
+    /// See [Sender::subscribe] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use unsync::broadcast;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mut sender = broadcast::shared::<String>(1);
+    ///
+    /// let mut sub1 = sender.subscribe();
+    /// let mut sub2 = sender.subscribe();
+    ///
+    /// let (result, s1, s2) = tokio::join!(sender.send(String::from("hello")), sub1.recv(), sub2.recv());
+    ///
+    /// let (s1, s2) = (s1.unwrap(), s2.unwrap());
+    /// assert_eq!(result, 2);
+    /// assert_eq!(*s1, "hello");
+    /// assert!(Rc::ptr_eq(&s1, &s2));
+    /// # }
+    /// ```
+    pub fn subscribe(&mut self) -> Receiver<Rc<T>> {
+        self.inner.subscribe()
+    }
+
+    /// Get a count on the number of subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.inner.subscribers()
+    }
+
+    /// Try to send a value to all subscribers in a non-blocking manner.
+    ///
+    /// See [Sender::try_send] for more information.
+    pub fn try_send(&mut self, value: T) -> Result<usize, UnderCapacity> {
+        self.inner.try_send(Rc::new(value))
+    }
+
+    /// Try to send a value to all subscribers in a non-blocking manner, handing
+    /// the value back if it couldn't be sent.
+    ///
+    /// See [Sender::try_send_all] for more information.
+    pub fn try_send_all(&mut self, value: T) -> Result<usize, TrySendError<T>> {
+        // The value is never shared with any subscriber on errors.
+        let unwrap = |value| match Rc::try_unwrap(value) {
+            Ok(value) => value,
+            Err(_) => unreachable!("value should not be shared"),
+        };
+
+        match self.inner.try_send_all(Rc::new(value)) {
+            Ok(n) => Ok(n),
+            Err(TrySendError::Full(value)) => Err(TrySendError::Full(unwrap(value))),
+            Err(TrySendError::Closed(value)) => Err(TrySendError::Closed(unwrap(value))),
+        }
+    }
+
+    /// Send a message on the channel, waiting until every subscriber has the
+    /// capacity to receive it.
+    ///
+    /// See [Sender::send] for more information.
+    pub fn send(&mut self, value: T) -> SendFut<'_, Rc<T>> {
+        self.inner.send(Rc::new(value))
+    }
 }
 
-/// Setup a broadcast channel which is unbounded.
+/// Setup a broadcast channel which delivers messages as an [Rc], and retains
+/// at most `capacity` messages which haven't been received by every
+/// subscriber.
+///
+/// Unlike [channel], this doesn't require the message to implement [Clone].
 ///
 /// # Panics
 ///
 /// Panics if `capacity` is specified as 0.
-pub fn unbounded<T>() -> Sender<T>
-where
-    T: Clone,
-{
-    let inner = BroadRc::new(Shared {
-        id: 0,
-        sender: None,
-        receivers: slab::Slab::new(),
-        capacity: None,
-    });
-
-    Sender { inner }
+///
+/// # Examples
+///
+/// ```
+/// use unsync::broadcast;
+///
+/// struct Event(u32);
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let mut tx = broadcast::shared::<Event>(4);
+/// let mut sub = tx.subscribe();
+///
+/// assert_eq!(tx.try_send(Event(42)), Ok(1));
+/// drop(tx);
+///
+/// assert_eq!(sub.recv().await.map(|event| event.0), Some(42));
+/// assert!(sub.recv().await.is_none());
+/// # }
+/// ```
+pub fn shared<T>(capacity: usize) -> SharedSender<T> {
+    SharedSender {
+        inner: channel(capacity),
+    }
 }
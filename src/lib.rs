@@ -55,6 +55,22 @@
 //!
 //! <br>
 //!
+//! # Features
+//!
+//! * `std` (default) - Implements `std::error::Error` for error types. When
+//!   disabled the crate is `#![no_std]` and only depends on `alloc`.
+//! * `futures-core` - Implements [Stream] for the [spsc] and [broadcast]
+//!   receivers, and [FusedFuture] / [FusedStream] where appropriate.
+//! * `futures-sink` - Implements [Sink] for the [spsc] and [broadcast]
+//!   senders.
+//!
+//! [Stream]: https://docs.rs/futures-core/0.3/futures_core/stream/trait.Stream.html
+//! [FusedFuture]: https://docs.rs/futures-core/0.3/futures_core/future/trait.FusedFuture.html
+//! [FusedStream]: https://docs.rs/futures-core/0.3/futures_core/stream/trait.FusedStream.html
+//! [Sink]: https://docs.rs/futures-sink/0.3/futures_sink/trait.Sink.html
+//!
+//! <br>
+//!
 //! # Examples
 //!
 //! ```
@@ -99,9 +115,22 @@
 //! [futures::unsync]: <https://docs.rs/futures/0.1.31/futures/unsync/index.html>
 
 #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
+pub mod barrier;
 mod bi_rc;
 mod broad_rc;
 pub mod broadcast;
+pub mod mpmc;
+pub mod mpsc;
+pub mod mutex;
+pub mod notify;
+pub mod once;
 pub mod oneshot;
+pub mod rwlock;
+pub mod semaphore;
 pub mod spsc;
+mod wait_list;
+pub mod watch;
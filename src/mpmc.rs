@@ -0,0 +1,445 @@
+//! An unsynchronized multi-producer, multi-consumer channel.
+//!
+//! Unlike [broadcast][crate::broadcast], each message sent over this channel
+//! is delivered to exactly *one* [Receiver]. This makes it useful as a work
+//! queue, where a pool of tasks share the same queue and each message is taken
+//! by whichever task is free to handle it.
+//!
+//! Both the [Sender] and the [Receiver] can be cloned. The channel is closed
+//! once every [Sender] has been dropped, after which receivers drain what's
+//! left in the buffer before receiving `None`.
+//!
+//! Blocked senders and receivers are woken up in the order in which they
+//! started waiting.
+//!
+//! This allocates storage internally to maintain shared state between the
+//! [Sender]s and [Receiver]s.
+
+use alloc::collections::VecDeque;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::future::Future;
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::broad_rc::{BroadRc, BroadWeak};
+use crate::wait_list::WaitList;
+
+/// Error raised when sending a message over the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SendError<T>(pub T);
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "channel disconnected")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SendError<T> where T: fmt::Debug {}
+
+/// Interior shared state.
+struct Shared<T> {
+    /// Senders waiting for capacity, in the order in which they started
+    /// waiting.
+    senders: WaitList<()>,
+    /// Receivers waiting for messages, in the order in which they started
+    /// waiting.
+    receivers: WaitList<()>,
+    /// Buffered messages.
+    buf: VecDeque<T>,
+    /// The capacity of the channel, or `None` if it's unbounded.
+    capacity: Option<NonZeroUsize>,
+}
+
+impl<T> Shared<T> {
+    /// Test if the current channel is at capacity.
+    fn at_capacity(&self) -> bool {
+        matches!(self.capacity, Some(capacity) if self.buf.len() >= capacity.get())
+    }
+
+    /// Push a value into the buffer and notify the next receiver in line.
+    fn push(&mut self, value: T) {
+        self.buf.push_back(value);
+        self.receivers.wake_front();
+    }
+}
+
+/// Sender end of the channel created through [channel] or [unbounded].
+///
+/// Senders can be cloned to allow more than one task to send on the same
+/// channel.
+pub struct Sender<T> {
+    inner: BroadWeak<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Try to send a message on this channel without blocking.
+    ///
+    /// This will succeed if there is sufficient capacity to send, but fail
+    /// otherwise. To preserve fairness this also fails if there are other
+    /// senders which are currently waiting for capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, rx) = unsync::mpmc::channel(2);
+    ///
+    /// assert!(tx.try_send(1).is_ok());
+    /// assert!(tx.try_send(2).is_ok());
+    /// assert!(tx.try_send(3).is_err());
+    ///
+    /// assert_eq!(rx.recv().await, Some(1));
+    /// assert!(tx.try_send(4).is_ok());
+    /// # }
+    /// ```
+    pub fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        unsafe {
+            let (inner, receivers_present) = self.inner.get_mut_unchecked();
+
+            if !receivers_present || !inner.senders.is_empty() || inner.at_capacity() {
+                return Err(SendError(value));
+            }
+
+            inner.push(value);
+            Ok(())
+        }
+    }
+
+    /// Send a message on this channel.
+    ///
+    /// If the channel is at capacity this waits until space becomes available.
+    /// Waiting senders are served in the order in which they started waiting.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SendError] if all [Receiver]s have been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, rx) = unsync::mpmc::channel(1);
+    /// let rx2 = rx.clone();
+    ///
+    /// let (result, a) = tokio::join!(tx.send(1), rx.recv());
+    /// assert!(result.is_ok());
+    /// assert_eq!(a, Some(1));
+    ///
+    /// drop(rx);
+    /// drop(rx2);
+    ///
+    /// assert_eq!(tx.send(2).await, Err(unsync::mpmc::SendError(2)));
+    /// # }
+    /// ```
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        Send {
+            inner: &self.inner,
+            value: Some(value),
+            key: None,
+        }
+        .await
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Future returned when sending a value through [Sender::send].
+struct Send<'a, T> {
+    inner: &'a BroadWeak<Shared<T>>,
+    value: Option<T>,
+    /// Our key in the wait list, if we are waiting for capacity.
+    key: Option<usize>,
+}
+
+impl<'a, T> Future for Send<'a, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let (inner, receivers_present) = this.inner.get_mut_unchecked();
+
+            if !receivers_present {
+                if let Some(key) = this.key.take() {
+                    inner.senders.remove(key);
+                }
+
+                let value = this.value.take().expect("future already completed");
+                return Poll::Ready(Err(SendError(value)));
+            }
+
+            // Only the sender at the front of the queue is allowed to make
+            // progress, anyone else has to wait for their turn.
+            let our_turn = match this.key {
+                Some(key) => inner.senders.is_front(key),
+                None => inner.senders.is_empty(),
+            };
+
+            if !our_turn || inner.at_capacity() {
+                match this.key {
+                    Some(key) => inner.senders.register(key, cx.waker()),
+                    None => this.key = Some(inner.senders.push_back((), cx.waker())),
+                }
+
+                return Poll::Pending;
+            }
+
+            if let Some(key) = this.key.take() {
+                inner.senders.remove(key);
+            }
+
+            inner.push(this.value.take().expect("future already completed"));
+
+            // Pass on any remaining capacity to the next sender in line.
+            if !inner.at_capacity() {
+                inner.senders.wake_front();
+            }
+
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T> Drop for Send<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            unsafe {
+                let (inner, _) = self.inner.get_mut_unchecked();
+                inner.senders.remove(key);
+
+                if !inner.at_capacity() {
+                    inner.senders.wake_front();
+                }
+            }
+        }
+    }
+}
+
+/// Receiver end of the channel created through [channel] or [unbounded].
+///
+/// Receivers can be cloned, each message is delivered to exactly one of them.
+pub struct Receiver<T> {
+    inner: BroadRc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive a message on this channel.
+    ///
+    /// This returns `None` once all [Sender]s have been dropped and the buffer
+    /// has been drained. Waiting receivers are served in the order in which
+    /// they started waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tokio::task;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+    /// let (tx, rx) = unsync::mpmc::channel(4);
+    ///
+    /// let local = task::LocalSet::new();
+    ///
+    /// let collected = local.run_until(async move {
+    ///     let mut workers = Vec::new();
+    ///
+    ///     for _ in 0..3 {
+    ///         let rx = rx.clone();
+    ///
+    ///         workers.push(task::spawn_local(async move {
+    ///             let mut out = Vec::new();
+    ///
+    ///             while let Some(value) = rx.recv().await {
+    ///                 out.push(value);
+    ///             }
+    ///
+    ///             out
+    ///         }));
+    ///     }
+    ///
+    ///     for n in 0..10 {
+    ///         let _ = tx.send(n).await;
+    ///     }
+    ///
+    ///     drop(tx);
+    ///
+    ///     let mut out = Vec::new();
+    ///
+    ///     for worker in workers {
+    ///         out.extend(worker.await?);
+    ///     }
+    ///
+    ///     Ok::<_, task::JoinError>(out)
+    /// }).await?;
+    ///
+    /// let mut collected = collected;
+    /// collected.sort();
+    /// assert_eq!(collected, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    /// # Ok(()) }
+    /// ```
+    pub async fn recv(&self) -> Option<T> {
+        Recv {
+            inner: &self.inner,
+            key: None,
+        }
+        .await
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Future returned when receiving through [Receiver::recv].
+struct Recv<'a, T> {
+    inner: &'a BroadRc<Shared<T>>,
+    /// Our key in the wait list, if we are waiting for a message.
+    key: Option<usize>,
+}
+
+impl<'a, T> Future for Recv<'a, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let (inner, senders_present) = this.inner.get_mut_unchecked();
+
+            let our_turn = match this.key {
+                Some(key) => inner.receivers.is_front(key),
+                None => inner.receivers.is_empty(),
+            };
+
+            if our_turn {
+                if let Some(value) = inner.buf.pop_front() {
+                    if let Some(key) = this.key.take() {
+                        inner.receivers.remove(key);
+                    }
+
+                    inner.senders.wake_front();
+
+                    // Pass on any remaining messages to the next receiver in
+                    // line, or let it know that the channel is closed.
+                    if !inner.buf.is_empty() || !senders_present {
+                        inner.receivers.wake_front();
+                    }
+
+                    return Poll::Ready(Some(value));
+                }
+            }
+
+            if !senders_present && inner.buf.is_empty() {
+                if let Some(key) = this.key.take() {
+                    inner.receivers.remove(key);
+                }
+
+                // Let the next receiver in line know that the channel is
+                // closed.
+                inner.receivers.wake_front();
+                return Poll::Ready(None);
+            }
+
+            match this.key {
+                Some(key) => inner.receivers.register(key, cx.waker()),
+                None => this.key = Some(inner.receivers.push_back((), cx.waker())),
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for Recv<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            unsafe {
+                let (inner, senders_present) = self.inner.get_mut_unchecked();
+                inner.receivers.remove(key);
+
+                // We might've been woken up to receive a message, so make sure
+                // it's passed on to the next receiver in line.
+                if !inner.buf.is_empty() || !senders_present {
+                    inner.receivers.wake_front();
+                }
+            }
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // Only the last sender closes the channel.
+            if self.inner.weak_count() != 1 {
+                return;
+            }
+
+            self.inner.get_mut_unchecked().0.receivers.wake_all();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // Only the last receiver closes the channel.
+            if self.inner.strong_count() != 1 {
+                return;
+            }
+
+            self.inner.get_mut_unchecked().0.senders.wake_all();
+        }
+    }
+}
+
+/// Setup a mpmc with the given capacity.
+///
+/// Senders are capable of sending without blocking up until `capacity` number
+/// of elements have been buffered.
+///
+/// # Panics
+///
+/// Panics if `capacity` is set to `0`.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let capacity = NonZeroUsize::new(capacity).expect("capacity cannot be 0");
+    new(Some(capacity))
+}
+
+/// Setup a mpmc with an unbounded capacity.
+///
+/// Sending through this channel will never block.
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    new(None)
+}
+
+fn new<T>(capacity: Option<NonZeroUsize>) -> (Sender<T>, Receiver<T>) {
+    let buf = match capacity {
+        Some(capacity) => VecDeque::with_capacity(capacity.get()),
+        None => VecDeque::new(),
+    };
+
+    let inner = BroadRc::new(Shared {
+        senders: WaitList::new(),
+        receivers: WaitList::new(),
+        buf,
+        capacity,
+    });
+
+    let tx = Sender {
+        inner: inner.weak(),
+    };
+
+    let rx = Receiver { inner };
+    (tx, rx)
+}
@@ -0,0 +1,388 @@
+//! An unsynchronized multi-producer, single-consumer channel.
+//!
+//! This works like [spsc][crate::spsc], except that the [Sender] can be cloned
+//! so that any number of tasks can feed the same [Receiver]. The channel is
+//! closed once every [Sender] has been dropped.
+//!
+//! Senders which are blocked waiting for capacity are woken up in the order in
+//! which they started waiting, so that a busy sender can't starve out the
+//! others.
+//!
+//! This allocates storage internally to maintain shared state between the
+//! [Sender]s and [Receiver].
+
+use alloc::collections::VecDeque;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::future::Future;
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+use crate::broad_rc::{BroadRc, BroadWeak};
+use crate::wait_list::WaitList;
+
+/// Error raised when sending a message over the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SendError<T>(pub T);
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "channel disconnected")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SendError<T> where T: fmt::Debug {}
+
+/// Interior shared state.
+struct Shared<T> {
+    /// Senders waiting for capacity, in the order in which they started
+    /// waiting.
+    senders: WaitList<()>,
+    /// Waker to wake once receiving is available.
+    rx: Option<Waker>,
+    /// Buffered messages.
+    buf: VecDeque<T>,
+    /// The capacity of the channel, or `None` if it's unbounded.
+    capacity: Option<NonZeroUsize>,
+}
+
+impl<T> Shared<T> {
+    /// Test if the current channel is at capacity.
+    fn at_capacity(&self) -> bool {
+        matches!(self.capacity, Some(capacity) if self.buf.len() >= capacity.get())
+    }
+
+    /// Push a value into the buffer and notify the receiver.
+    fn push(&mut self, value: T) {
+        self.buf.push_back(value);
+
+        if let Some(waker) = &self.rx {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+/// Sender end of the channel created through [channel] or [unbounded].
+///
+/// Senders can be cloned to allow more than one task to send on the same
+/// channel.
+pub struct Sender<T> {
+    inner: BroadWeak<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Try to send a message on this channel without blocking.
+    ///
+    /// This will succeed if there is sufficient capacity to send, but fail
+    /// otherwise. To preserve fairness this also fails if there are other
+    /// senders which are currently waiting for capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, mut rx) = unsync::mpsc::channel(2);
+    /// let tx2 = tx.clone();
+    ///
+    /// assert!(tx.try_send(1).is_ok());
+    /// assert!(tx2.try_send(2).is_ok());
+    /// assert!(tx.try_send(3).is_err());
+    ///
+    /// assert_eq!(rx.recv().await, Some(1));
+    /// assert!(tx2.try_send(4).is_ok());
+    ///
+    /// drop((tx, tx2));
+    ///
+    /// let mut collected = Vec::new();
+    ///
+    /// while let Some(value) = rx.recv().await {
+    ///     collected.push(value);
+    /// }
+    ///
+    /// assert_eq!(collected, vec![2, 4]);
+    /// # }
+    /// ```
+    pub fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        unsafe {
+            let (inner, receiver_present) = self.inner.get_mut_unchecked();
+
+            if !receiver_present || !inner.senders.is_empty() || inner.at_capacity() {
+                return Err(SendError(value));
+            }
+
+            inner.push(value);
+            Ok(())
+        }
+    }
+
+    /// Send a message on this channel.
+    ///
+    /// If the channel is at capacity this waits until space becomes available.
+    /// Waiting senders are served in the order in which they started waiting.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SendError] if the [Receiver] has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tokio::task;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+    /// let (tx, mut rx) = unsync::mpsc::channel(1);
+    ///
+    /// let local = task::LocalSet::new();
+    ///
+    /// let collected = local.run_until(async move {
+    ///     for n in 0..2 {
+    ///         let tx = tx.clone();
+    ///
+    ///         task::spawn_local(async move {
+    ///             for m in 0..5 {
+    ///                 let _ = tx.send(n * 5 + m).await;
+    ///             }
+    ///         });
+    ///     }
+    ///
+    ///     // Drop the original sender so that the channel "ends" once the
+    ///     // spawned senders are done.
+    ///     drop(tx);
+    ///
+    ///     let mut out = Vec::new();
+    ///
+    ///     while let Some(value) = rx.recv().await {
+    ///         out.push(value);
+    ///     }
+    ///
+    ///     out
+    /// }).await;
+    ///
+    /// let mut collected = collected;
+    /// collected.sort();
+    /// assert_eq!(collected, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    /// # Ok(()) }
+    /// ```
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        Send {
+            inner: &self.inner,
+            value: Some(value),
+            key: None,
+        }
+        .await
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Future returned when sending a value through [Sender::send].
+struct Send<'a, T> {
+    inner: &'a BroadWeak<Shared<T>>,
+    value: Option<T>,
+    /// Our key in the wait list, if we are waiting for capacity.
+    key: Option<usize>,
+}
+
+impl<'a, T> Future for Send<'a, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let (inner, receiver_present) = this.inner.get_mut_unchecked();
+
+            if !receiver_present {
+                if let Some(key) = this.key.take() {
+                    inner.senders.remove(key);
+                }
+
+                let value = this.value.take().expect("future already completed");
+                return Poll::Ready(Err(SendError(value)));
+            }
+
+            // Only the sender at the front of the queue is allowed to make
+            // progress, anyone else has to wait for their turn.
+            let our_turn = match this.key {
+                Some(key) => inner.senders.is_front(key),
+                None => inner.senders.is_empty(),
+            };
+
+            if !our_turn || inner.at_capacity() {
+                match this.key {
+                    Some(key) => inner.senders.register(key, cx.waker()),
+                    None => this.key = Some(inner.senders.push_back((), cx.waker())),
+                }
+
+                return Poll::Pending;
+            }
+
+            if let Some(key) = this.key.take() {
+                inner.senders.remove(key);
+            }
+
+            inner.push(this.value.take().expect("future already completed"));
+
+            // Pass on any remaining capacity to the next sender in line.
+            if !inner.at_capacity() {
+                inner.senders.wake_front();
+            }
+
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T> Drop for Send<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            unsafe {
+                let (inner, _) = self.inner.get_mut_unchecked();
+                inner.senders.remove(key);
+
+                // We might've been woken up to make use of capacity which is
+                // now available, so make sure it's passed on to the next
+                // sender in line.
+                if !inner.at_capacity() {
+                    inner.senders.wake_front();
+                }
+            }
+        }
+    }
+}
+
+/// Receiver end of the channel created through [channel] or [unbounded].
+pub struct Receiver<T> {
+    inner: BroadRc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive a message on this channel.
+    ///
+    /// This returns `None` once all [Sender]s have been dropped and the buffer
+    /// has been drained.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, mut rx) = unsync::mpsc::unbounded();
+    /// let tx2 = tx.clone();
+    ///
+    /// let (a, b) = tokio::join!(tx.send(1), tx2.send(2));
+    /// assert!(a.is_ok() && b.is_ok());
+    ///
+    /// drop(tx);
+    /// drop(tx2);
+    ///
+    /// assert_eq!(rx.recv().await, Some(1));
+    /// assert_eq!(rx.recv().await, Some(2));
+    /// assert_eq!(rx.recv().await, None);
+    /// # }
+    /// ```
+    pub async fn recv(&mut self) -> Option<T> {
+        Recv { inner: &self.inner }.await
+    }
+}
+
+/// Future returned when receiving through [Receiver::recv].
+struct Recv<'a, T> {
+    inner: &'a BroadRc<Shared<T>>,
+}
+
+impl<'a, T> Future for Recv<'a, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let (inner, senders_present) = this.inner.get_mut_unchecked();
+
+            if let Some(value) = inner.buf.pop_front() {
+                inner.senders.wake_front();
+                return Poll::Ready(Some(value));
+            }
+
+            if !senders_present {
+                inner.rx = None;
+                return Poll::Ready(None);
+            }
+
+            if !matches!(&inner.rx, Some(w) if w.will_wake(cx.waker())) {
+                inner.rx = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // Only the last sender closes the channel.
+            if self.inner.weak_count() != 1 {
+                return;
+            }
+
+            if let Some(waker) = self.inner.get_mut_unchecked().0.rx.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.inner.get_mut_unchecked().0.senders.wake_all();
+        }
+    }
+}
+
+/// Setup a mpsc with the given capacity.
+///
+/// Senders are capable of sending without blocking up until `capacity` number
+/// of elements have been buffered.
+///
+/// # Panics
+///
+/// Panics if `capacity` is set to `0`.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let capacity = NonZeroUsize::new(capacity).expect("capacity cannot be 0");
+    new(Some(capacity))
+}
+
+/// Setup a mpsc with an unbounded capacity.
+///
+/// Sending through this channel will never block.
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    new(None)
+}
+
+fn new<T>(capacity: Option<NonZeroUsize>) -> (Sender<T>, Receiver<T>) {
+    let buf = match capacity {
+        Some(capacity) => VecDeque::with_capacity(capacity.get()),
+        None => VecDeque::new(),
+    };
+
+    let inner = BroadRc::new(Shared {
+        senders: WaitList::new(),
+        rx: None,
+        buf,
+        capacity,
+    });
+
+    let tx = Sender {
+        inner: inner.weak(),
+    };
+
+    let rx = Receiver { inner };
+    (tx, rx)
+}
@@ -0,0 +1,466 @@
+//! An unsynchronized async mutex.
+//!
+//! This can be used to guard state which needs to be held across `.await`
+//! points in tasks running on the same thread. If the lock doesn't need to be
+//! held across an `.await`, a [RefCell][std::cell::RefCell] is cheaper and
+//! should be preferred.
+//!
+//! Tasks waiting for the lock acquire it in the order in which they started
+//! waiting.
+
+use alloc::rc::Rc;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::fmt::{self, Display, Formatter};
+use core::future::Future;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::wait_list::WaitList;
+
+/// An async mutex.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use tokio::task;
+/// use unsync::mutex::Mutex;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+/// let mutex = Rc::new(Mutex::new(Vec::new()));
+///
+/// let local = task::LocalSet::new();
+///
+/// local.run_until(async {
+///     let mut tasks = Vec::new();
+///
+///     for n in 0..4 {
+///         let mutex = mutex.clone();
+///
+///         tasks.push(task::spawn_local(async move {
+///             let mut guard = mutex.lock().await;
+///             // Yielding while holding the lock forces other tasks to wait.
+///             task::yield_now().await;
+///             guard.push(n);
+///         }));
+///     }
+///
+///     for t in tasks {
+///         t.await?;
+///     }
+///
+///     Ok::<_, task::JoinError>(())
+/// }).await?;
+///
+/// assert_eq!(*mutex.lock().await, vec![0, 1, 2, 3]);
+/// # Ok(()) }
+/// ```
+pub struct Mutex<T: ?Sized> {
+    /// Indicates if the mutex is locked.
+    locked: Cell<bool>,
+    /// Tasks waiting to acquire the lock.
+    waiters: RefCell<WaitList<()>>,
+    /// The guarded value.
+    value: UnsafeCell<T>,
+}
+
+/// Error raised by [Mutex::try_lock] if the lock is currently held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TryLockError;
+
+impl Display for TryLockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "mutex is locked")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryLockError {}
+
+impl<T> Mutex<T> {
+    /// Construct a new unlocked mutex guarding the given value.
+    pub fn new(value: T) -> Self {
+        Self {
+            locked: Cell::new(false),
+            waiters: RefCell::new(WaitList::new()),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the mutex, returning the guarded value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Lock the mutex, waiting until it becomes available.
+    ///
+    /// Tasks waiting for the lock acquire it in the order in which they
+    /// started waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::mutex::Mutex;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mutex = Mutex::new(1);
+    ///
+    /// let mut guard = mutex.lock().await;
+    /// *guard += 1;
+    /// drop(guard);
+    ///
+    /// assert_eq!(*mutex.lock().await, 2);
+    /// # }
+    /// ```
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        Lock {
+            mutex: self,
+            key: None,
+        }
+        .await;
+
+        MutexGuard { mutex: self }
+    }
+
+    /// Try to lock the mutex without waiting.
+    ///
+    /// To preserve fairness this fails if other tasks are waiting for the
+    /// lock, even if it's not currently held.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryLockError] if the lock couldn't be acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::mutex::{Mutex, TryLockError};
+    ///
+    /// let mutex = Mutex::new(1);
+    ///
+    /// let guard = mutex.try_lock().unwrap();
+    /// assert!(matches!(mutex.try_lock(), Err(TryLockError)));
+    /// drop(guard);
+    ///
+    /// assert!(mutex.try_lock().is_ok());
+    /// ```
+    pub fn try_lock(&self) -> Result<MutexGuard<'_, T>, TryLockError> {
+        if !self.try_acquire() {
+            return Err(TryLockError);
+        }
+
+        Ok(MutexGuard { mutex: self })
+    }
+
+    /// Lock the mutex through an [Rc], returning a guard which keeps the mutex
+    /// alive and isn't bound to a lifetime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use unsync::mutex::{Mutex, OwnedMutexGuard};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mutex = Rc::new(Mutex::new(1));
+    ///
+    /// let mut guard: OwnedMutexGuard<u32> = mutex.clone().lock_owned().await;
+    /// *guard += 1;
+    /// drop(guard);
+    ///
+    /// assert_eq!(*mutex.lock().await, 2);
+    /// # }
+    /// ```
+    pub async fn lock_owned(self: Rc<Self>) -> OwnedMutexGuard<T> {
+        Lock {
+            mutex: &self,
+            key: None,
+        }
+        .await;
+
+        OwnedMutexGuard { mutex: self }
+    }
+
+    /// Try to lock the mutex through an [Rc] without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryLockError] if the lock couldn't be acquired. See
+    /// [Mutex::try_lock].
+    pub fn try_lock_owned(self: Rc<Self>) -> Result<OwnedMutexGuard<T>, TryLockError> {
+        if !self.try_acquire() {
+            return Err(TryLockError);
+        }
+
+        Ok(OwnedMutexGuard { mutex: self })
+    }
+
+    /// Get a mutable reference to the guarded value.
+    ///
+    /// No locking is necessary since this requires exclusive access to the
+    /// mutex.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Try to acquire the lock, respecting any waiters.
+    fn try_acquire(&self) -> bool {
+        if self.locked.get() || !self.waiters.borrow().is_empty() {
+            return false;
+        }
+
+        self.locked.set(true);
+        true
+    }
+
+    /// Release the lock and wake up the next waiter in line.
+    fn unlock(&self) {
+        self.locked.set(false);
+        self.waiters.borrow().wake_front();
+    }
+}
+
+impl<T> Default for Mutex<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Future associated with acquiring the lock.
+struct Lock<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+    /// Our key in the wait list, if we are waiting for the lock.
+    key: Option<usize>,
+}
+
+impl<T: ?Sized> Future for Lock<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let mutex = this.mutex;
+        let mut waiters = mutex.waiters.borrow_mut();
+
+        // Only the task at the front of the queue is allowed to acquire the
+        // lock, anyone else has to wait for their turn.
+        let our_turn = match this.key {
+            Some(key) => waiters.is_front(key),
+            None => waiters.is_empty(),
+        };
+
+        if our_turn && !mutex.locked.get() {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            mutex.locked.set(true);
+            return Poll::Ready(());
+        }
+
+        match this.key {
+            Some(key) => waiters.register(key, cx.waker()),
+            None => this.key = Some(waiters.push_back((), cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T: ?Sized> Drop for Lock<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut waiters = self.mutex.waiters.borrow_mut();
+            waiters.remove(key);
+
+            // We might've been woken up to acquire the lock, so make sure it's
+            // passed on to the next waiter in line.
+            if !self.mutex.locked.get() {
+                waiters.wake_front();
+            }
+        }
+    }
+}
+
+/// A guard returned by [Mutex::lock] and [Mutex::try_lock].
+///
+/// The lock is released when the guard is dropped.
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<'a, T: ?Sized> MutexGuard<'a, T> {
+    /// Make a new [MappedMutexGuard] for a component of the locked value.
+    ///
+    /// This is an associated function which needs to be called as
+    /// `MutexGuard::map(guard, ..)`, so that it doesn't conflict with methods
+    /// on the guarded value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::mutex::{Mutex, MutexGuard};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let mutex = Mutex::new((1, String::from("hello")));
+    ///
+    /// let mut guard = MutexGuard::map(mutex.lock().await, |(_, s)| s);
+    /// guard.push_str(" world");
+    /// drop(guard);
+    ///
+    /// assert_eq!(mutex.lock().await.1, "hello world");
+    /// # }
+    /// ```
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> MappedMutexGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let mutex = this.mutex;
+
+        // Safety: the guard has exclusive access to the value.
+        let value = f(unsafe { &mut *mutex.value.get() });
+
+        // Only forget the guard once `f` has returned, so that the lock is
+        // released if it panics.
+        mem::forget(this);
+
+        MappedMutexGuard {
+            locked: &mutex.locked,
+            waiters: &mutex.waiters,
+            value,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: the guard has exclusive access to the value.
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: the guard has exclusive access to the value.
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.unlock();
+    }
+}
+
+/// A guard for a component of a locked value, returned by [MutexGuard::map].
+///
+/// The lock is released when the guard is dropped.
+pub struct MappedMutexGuard<'a, T: ?Sized> {
+    locked: &'a Cell<bool>,
+    waiters: &'a RefCell<WaitList<()>>,
+    value: &'a mut T,
+}
+
+impl<'a, T: ?Sized> MappedMutexGuard<'a, T> {
+    /// Make a new [MappedMutexGuard] for a component of the locked value.
+    ///
+    /// This is an associated function which needs to be called as
+    /// `MappedMutexGuard::map(guard, ..)`, so that it doesn't conflict with
+    /// methods on the guarded value.
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> MappedMutexGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let value: *mut T = &mut *this.value;
+
+        // Safety: the guard has exclusive access to the value, and is
+        // forgotten below without accessing it again.
+        let value = f(unsafe { &mut *value });
+
+        let locked = this.locked;
+        let waiters = this.waiters;
+
+        // Only forget the guard once `f` has returned, so that the lock is
+        // released if it panics.
+        mem::forget(this);
+
+        MappedMutexGuard {
+            locked,
+            waiters,
+            value,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for MappedMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<T: ?Sized> DerefMut for MappedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value
+    }
+}
+
+impl<T: ?Sized> Drop for MappedMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.locked.set(false);
+        self.waiters.borrow().wake_front();
+    }
+}
+
+/// An owned guard returned by [Mutex::lock_owned] and
+/// [Mutex::try_lock_owned].
+///
+/// This keeps the [Mutex] alive, so it's not bound to a lifetime. The lock is
+/// released when the guard is dropped.
+pub struct OwnedMutexGuard<T: ?Sized> {
+    mutex: Rc<Mutex<T>>,
+}
+
+impl<T: ?Sized> OwnedMutexGuard<T> {
+    /// Get the [Mutex] that this guard is locking.
+    pub fn mutex(this: &Self) -> &Rc<Mutex<T>> {
+        &this.mutex
+    }
+}
+
+impl<T: ?Sized> Deref for OwnedMutexGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: the guard has exclusive access to the value.
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for OwnedMutexGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: the guard has exclusive access to the value.
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedMutexGuard<T> {
+    fn drop(&mut self) {
+        self.mutex.unlock();
+    }
+}
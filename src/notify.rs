@@ -0,0 +1,229 @@
+//! An unsynchronized primitive for notifying tasks without a payload.
+//!
+//! This is useful when tasks only need to be told that *something* happened,
+//! such as state having changed, and they can go and look for themselves.
+//!
+//! No storage is allocated until a task actually needs to wait.
+
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::wait_list::WaitList;
+
+/// Notify a single task, or all tasks which are waiting.
+///
+/// [Notify::notify_one] stores a permit if no task is currently waiting, so
+/// that the next call to [Notify::notified] completes immediately. At most one
+/// permit is stored at a time.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use tokio::task;
+/// use unsync::notify::Notify;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+/// let notify = Rc::new(Notify::new());
+///
+/// let local = task::LocalSet::new();
+///
+/// local.run_until(async move {
+///     let waiter = task::spawn_local({
+///         let notify = notify.clone();
+///
+///         async move {
+///             notify.notified().await;
+///         }
+///     });
+///
+///     notify.notify_one();
+///     waiter.await
+/// }).await?;
+/// # Ok(()) }
+/// ```
+pub struct Notify {
+    /// Indicates if a permit is stored.
+    permit: Cell<bool>,
+    /// Incremented every time [Notify::notify_waiters] is called.
+    generation: Cell<u64>,
+    /// Tasks waiting to be notified. The associated data indicates if the
+    /// waiter has been notified through [Notify::notify_one].
+    waiters: RefCell<WaitList<bool>>,
+}
+
+impl Notify {
+    /// Construct a new notify primitive without a stored permit.
+    pub fn new() -> Self {
+        Self {
+            permit: Cell::new(false),
+            generation: Cell::new(0),
+            waiters: RefCell::new(WaitList::new()),
+        }
+    }
+
+    /// Wait for a notification.
+    ///
+    /// The returned future completes once it is notified by
+    /// [Notify::notify_one] or [Notify::notify_waiters], or immediately when
+    /// polled if a permit is stored.
+    ///
+    /// Note that the future is considered to be waiting from the moment it's
+    /// created as far as [Notify::notify_waiters] is concerned, but it's only
+    /// placed in line for [Notify::notify_one] once it's first polled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::notify::Notify;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let notify = Notify::new();
+    ///
+    /// // The permit is stored until someone waits.
+    /// notify.notify_one();
+    /// notify.notified().await;
+    /// # }
+    /// ```
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            generation: self.generation.get(),
+            key: None,
+        }
+    }
+
+    /// Notify the task which has been waiting the longest.
+    ///
+    /// If no task is waiting, a permit is stored so that the next call to
+    /// [Notify::notified] completes immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::notify::Notify;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let notify = Notify::new();
+    ///
+    /// let (_, ()) = tokio::join!(async { notify.notify_one() }, notify.notified());
+    /// # }
+    /// ```
+    pub fn notify_one(&self) {
+        let mut waiters = self.waiters.borrow_mut();
+
+        match waiters.pop_front() {
+            Some(key) => {
+                *waiters.get_mut(key) = true;
+            }
+            None => {
+                self.permit.set(true);
+            }
+        }
+    }
+
+    /// Notify every task which is currently waiting.
+    ///
+    /// This includes any [Notified] futures which have been created but not
+    /// yet polled. No permit is stored, so later calls to [Notify::notified]
+    /// are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::notify::Notify;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let notify = Notify::new();
+    ///
+    /// let a = notify.notified();
+    /// let b = notify.notified();
+    ///
+    /// notify.notify_waiters();
+    ///
+    /// a.await;
+    /// b.await;
+    /// # }
+    /// ```
+    pub fn notify_waiters(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+        self.waiters.borrow_mut().wake_all();
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Future returned by [Notify::notified].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    /// The generation of [Notify::notify_waiters] calls this future was
+    /// created in.
+    generation: u64,
+    /// Our key in the wait list, if we are waiting.
+    key: Option<usize>,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let notify = this.notify;
+        let mut waiters = notify.waiters.borrow_mut();
+
+        if notify.generation.get() != this.generation {
+            if let Some(key) = this.key.take() {
+                // If we were notified through `notify_one` as well, pass it on
+                // so that it isn't lost.
+                if waiters.remove(key) {
+                    drop(waiters);
+                    notify.notify_one();
+                }
+            }
+
+            return Poll::Ready(());
+        }
+
+        match this.key {
+            Some(key) => {
+                if *waiters.get_mut(key) {
+                    waiters.remove(key);
+                    this.key = None;
+                    return Poll::Ready(());
+                }
+
+                waiters.register(key, cx.waker());
+            }
+            None => {
+                if notify.permit.replace(false) {
+                    return Poll::Ready(());
+                }
+
+                this.key = Some(waiters.push_back(false, cx.waker()));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let notified = self.notify.waiters.borrow_mut().remove(key);
+
+            // A notification which was meant for us but never observed is
+            // passed on to the next waiter.
+            if notified {
+                self.notify.notify_one();
+            }
+        }
+    }
+}
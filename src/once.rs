@@ -0,0 +1,397 @@
+//! Unsynchronized cells which are initialized asynchronously exactly once.
+//!
+//! If multiple tasks try to initialize a [OnceCell] at the same time, only one
+//! initializer runs while the others wait for it to complete. Should the
+//! running initializer fail or be dropped, the next waiting task gets to try
+//! its own.
+
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::convert::Infallible;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::wait_list::WaitList;
+
+/// Error raised by [OnceCell::set] when the cell is already initialized, or
+/// is currently being initialized.
+///
+/// # Examples
+///
+/// ```
+/// use unsync::once::{OnceCell, SetError};
+///
+/// let cell = OnceCell::new();
+/// assert!(cell.set(1).is_ok());
+/// assert_eq!(cell.set(2), Err(SetError(2)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SetError<T>(pub T);
+
+impl<T> Display for SetError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "cell already initialized")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SetError<T> where T: fmt::Debug {}
+
+/// A cell which can be asynchronously initialized once.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use tokio::task;
+/// use unsync::once::OnceCell;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+/// let config = Rc::new(OnceCell::new());
+/// let fetches = Rc::new(Cell::new(0));
+///
+/// let local = task::LocalSet::new();
+///
+/// local.run_until(async {
+///     let mut tasks = Vec::new();
+///
+///     for _ in 0..4 {
+///         let config = config.clone();
+///         let fetches = fetches.clone();
+///
+///         tasks.push(task::spawn_local(async move {
+///             let value = config.get_or_init(|| async {
+///                 fetches.set(fetches.get() + 1);
+///                 task::yield_now().await;
+///                 String::from("config")
+///             }).await;
+///
+///             assert_eq!(value, "config");
+///         }));
+///     }
+///
+///     for t in tasks {
+///         t.await?;
+///     }
+///
+///     Ok::<_, task::JoinError>(())
+/// }).await?;
+///
+/// assert_eq!(fetches.get(), 1);
+/// # Ok(()) }
+/// ```
+pub struct OnceCell<T> {
+    /// The value of the cell. This is only written to while it's empty and
+    /// we have exclusive access to initializing it.
+    value: UnsafeCell<Option<T>>,
+    /// Indicates if an initializer is currently running.
+    initializing: Cell<bool>,
+    /// Tasks waiting for their turn to initialize the cell.
+    waiters: RefCell<WaitList<()>>,
+}
+
+impl<T> OnceCell<T> {
+    /// Construct a new empty cell.
+    pub fn new() -> Self {
+        Self {
+            value: UnsafeCell::new(None),
+            initializing: Cell::new(false),
+            waiters: RefCell::new(WaitList::new()),
+        }
+    }
+
+    /// Get a reference to the value of the cell, if it's initialized.
+    pub fn get(&self) -> Option<&T> {
+        // SAFETY: The value is never modified through a shared reference once
+        // it has been set.
+        unsafe { (*self.value.get()).as_ref() }
+    }
+
+    /// Get a mutable reference to the value of the cell, if it's initialized.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.value.get_mut().as_mut()
+    }
+
+    /// Test if the cell has been initialized.
+    pub fn initialized(&self) -> bool {
+        self.get().is_some()
+    }
+
+    /// Set the value of the cell.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SetError] if the cell is already initialized, or if an
+    /// initializer is currently running.
+    pub fn set(&self, value: T) -> Result<(), SetError<T>> {
+        if self.initializing.get() || self.initialized() {
+            return Err(SetError(value));
+        }
+
+        // SAFETY: The cell is empty, so there are no outstanding references
+        // to its value.
+        unsafe {
+            *self.value.get() = Some(value);
+        }
+
+        self.waiters.borrow_mut().wake_all();
+        Ok(())
+    }
+
+    /// Get the value of the cell, initializing it with `init` if it's empty.
+    ///
+    /// If another task is currently initializing the cell this waits for it
+    /// to complete. Should that initializer be dropped before it completes,
+    /// the longest waiting task runs its own initializer instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::once::OnceCell;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let cell = OnceCell::new();
+    ///
+    /// assert_eq!(*cell.get_or_init(|| async { 1 }).await, 1);
+    /// assert_eq!(*cell.get_or_init(|| async { 2 }).await, 1);
+    /// # }
+    /// ```
+    pub async fn get_or_init<F, Fut>(&self, init: F) -> &T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let result = self
+            .get_or_try_init(|| async { Ok::<_, Infallible>(init().await) })
+            .await;
+
+        match result {
+            Ok(value) => value,
+            Err(error) => match error {},
+        }
+    }
+
+    /// Get the value of the cell, initializing it with the fallible `init` if
+    /// it's empty.
+    ///
+    /// This behaves like [OnceCell::get_or_init], except that if `init`
+    /// fails the cell is left empty and the next waiting task gets to try.
+    ///
+    /// # Errors
+    ///
+    /// Errors with the error raised by `init`, if it was run and failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::once::OnceCell;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let cell = OnceCell::new();
+    ///
+    /// assert_eq!(cell.get_or_try_init(|| async { Err("offline") }).await, Err("offline"));
+    /// assert!(cell.get().is_none());
+    ///
+    /// assert_eq!(cell.get_or_try_init(|| async { Ok::<_, &str>(1) }).await, Ok(&1));
+    /// # }
+    /// ```
+    pub async fn get_or_try_init<F, Fut, E>(&self, init: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+
+        let guard = match (Acquire {
+            cell: self,
+            key: None,
+        })
+        .await
+        {
+            Some(guard) => guard,
+            None => return Ok(self.get().expect("cell should be initialized")),
+        };
+
+        let value = init().await?;
+
+        // SAFETY: We hold the initialization guard and the cell is empty, so
+        // there are no outstanding references to its value.
+        unsafe {
+            *self.value.get() = Some(value);
+        }
+
+        drop(guard);
+        Ok(self.get().expect("cell should be initialized"))
+    }
+
+    /// Take the value out of the cell, leaving it empty.
+    pub fn take(&mut self) -> Option<T> {
+        self.value.get_mut().take()
+    }
+
+    /// Consume the cell, returning its value if it's initialized.
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for OnceCell<T> {
+    fn from(value: T) -> Self {
+        Self {
+            value: UnsafeCell::new(Some(value)),
+            initializing: Cell::new(false),
+            waiters: RefCell::new(WaitList::new()),
+        }
+    }
+}
+
+/// Future associated with waiting for the right to initialize a [OnceCell].
+///
+/// Resolves to `None` if the cell was initialized while waiting.
+struct Acquire<'a, T> {
+    cell: &'a OnceCell<T>,
+    /// Our key in the wait list, if we are waiting.
+    key: Option<usize>,
+}
+
+impl<'a, T> Future for Acquire<'a, T> {
+    type Output = Option<InitGuard<'a, T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let cell = this.cell;
+        let mut waiters = cell.waiters.borrow_mut();
+
+        if cell.initialized() {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            return Poll::Ready(None);
+        }
+
+        if !cell.initializing.get() {
+            let first = match this.key {
+                Some(key) => waiters.is_front(key),
+                None => waiters.is_empty(),
+            };
+
+            if first {
+                if let Some(key) = this.key.take() {
+                    waiters.remove(key);
+                }
+
+                cell.initializing.set(true);
+                return Poll::Ready(Some(InitGuard { cell }));
+            }
+        }
+
+        match this.key {
+            Some(key) => waiters.register(key, cx.waker()),
+            None => this.key = Some(waiters.push_back((), cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Acquire<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut waiters = self.cell.waiters.borrow_mut();
+            waiters.remove(key);
+
+            // We might have been next in line, so give the next waiter a
+            // chance to run its initializer.
+            if !self.cell.initializing.get() {
+                waiters.wake_front();
+            }
+        }
+    }
+}
+
+/// Guard held while running an initializer.
+///
+/// If the guard is dropped without the cell having been initialized, the next
+/// waiter is woken up so that it can run its own initializer.
+struct InitGuard<'a, T> {
+    cell: &'a OnceCell<T>,
+}
+
+impl<T> Drop for InitGuard<'_, T> {
+    fn drop(&mut self) {
+        self.cell.initializing.set(false);
+        let mut waiters = self.cell.waiters.borrow_mut();
+
+        if self.cell.initialized() {
+            waiters.wake_all();
+        } else {
+            waiters.wake_front();
+        }
+    }
+}
+
+/// A value which is asynchronously initialized on first access.
+///
+/// The initializer is stored so that it can be called again if an earlier
+/// attempt at initializing the value was dropped before it completed.
+///
+/// # Examples
+///
+/// ```
+/// use unsync::once::Lazy;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let lazy = Lazy::new(|| async { String::from("hello") });
+/// assert!(lazy.get().is_none());
+///
+/// assert_eq!(lazy.force().await, "hello");
+/// assert_eq!(lazy.get().map(String::as_str), Some("hello"));
+/// # }
+/// ```
+pub struct Lazy<T, F> {
+    cell: OnceCell<T>,
+    init: F,
+}
+
+impl<T, F> Lazy<T, F> {
+    /// Construct a new lazy value with the given initializer.
+    pub fn new(init: F) -> Self {
+        Self {
+            cell: OnceCell::new(),
+            init,
+        }
+    }
+
+    /// Get a reference to the value, if it has been initialized.
+    pub fn get(&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    /// Get the value, initializing it if this is the first access.
+    ///
+    /// If another task is currently initializing the value this waits for it
+    /// to complete.
+    pub async fn force<Fut>(&self) -> &T
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        self.cell.get_or_init(&self.init).await
+    }
+
+    /// Consume the lazy value, returning it if it has been initialized.
+    pub fn into_value(self) -> Option<T> {
+        self.cell.into_inner()
+    }
+}
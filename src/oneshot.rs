@@ -3,11 +3,10 @@
 //! This allocates storage internally to maintain shared state between the
 //! [Sender] and [Receiver].
 
-use std::error::Error;
-use std::fmt::{self, Debug, Display, Formatter};
-use std::future::Future;
-use std::pin::Pin;
-use std::task::{Context, Poll, Waker};
+use core::fmt::{self, Debug, Display, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
 
 use crate::bi_rc::BiRc;
 
@@ -27,20 +26,88 @@ use crate::bi_rc::BiRc;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SendError<T>(pub T);
 
+impl<T> SendError<T> {
+    /// Get the value which couldn't be sent.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
 impl<T> Display for SendError<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "channel disconnected")
     }
 }
 
-impl<T> Error for SendError<T> where T: Debug {}
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SendError<T> where T: fmt::Debug {}
+
+/// Error raised when receiving a message, because the [Sender] was dropped
+/// without sending one.
+///
+/// # Examples
+///
+/// ```
+/// use unsync::oneshot;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let (tx, rx) = oneshot::channel::<u32>();
+/// drop(tx);
+/// assert_eq!(rx.await, Err(oneshot::RecvError));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RecvError;
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "channel disconnected")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecvError {}
+
+/// Error raised when trying to receive a message without blocking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TryRecvError {
+    /// No message has been sent yet.
+    Empty,
+    /// The [Sender] was dropped without sending a message.
+    Closed,
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "channel empty"),
+            TryRecvError::Closed => write!(f, "channel disconnected"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryRecvError {}
 
 /// Interior shared state.
 struct Shared<T> {
     /// Waker to wake once value is set.
     waker: Option<Waker>,
+    /// Waker to wake once the receiver is dropped or closed.
+    closed: Option<Waker>,
     /// Test if the interior value is set.
     buf: Option<T>,
+    /// Indicates if the receiver has closed the channel through
+    /// [Receiver::close].
+    receiver_closed: bool,
+}
+
+impl<T> Shared<T> {
+    /// Test if a value can no longer be sent, because the receiver has been
+    /// dropped or closed.
+    fn is_closed(&self, both_present: bool) -> bool {
+        !both_present || self.receiver_closed
+    }
 }
 
 /// Sender end of the channel created through [channel].
@@ -56,7 +123,7 @@ impl<T> Sender<T> {
     /// # Errors
     ///
     /// This function raises [SendError] in case the receiver end of the channel
-    /// has been closed.
+    /// has been dropped or closed.
     ///
     /// ```
     /// use unsync::oneshot;
@@ -76,14 +143,14 @@ impl<T> Sender<T> {
     /// # #[tokio::main(flavor = "current_thread")] async fn main() {
     /// let (tx, rx) = oneshot::channel();
     /// assert!(tx.send(1).is_ok());
-    /// assert_eq!(rx.await, Some(1));
+    /// assert_eq!(rx.await, Ok(1));
     /// # }
     /// ```
     pub fn send(self, value: T) -> Result<(), SendError<T>> {
         unsafe {
             let (inner, both_present) = self.inner.get_mut_unchecked();
 
-            if !both_present {
+            if inner.is_closed(both_present) {
                 return Err(SendError(value));
             }
 
@@ -96,11 +163,109 @@ impl<T> Sender<T> {
             Ok(())
         }
     }
+
+    /// Test if the [Receiver] has been dropped or closed, in which case
+    /// sending will fail.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::oneshot;
+    ///
+    /// let (tx, rx) = oneshot::channel::<u32>();
+    /// assert!(!tx.is_closed());
+    /// drop(rx);
+    /// assert!(tx.is_closed());
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+            inner.is_closed(both_present)
+        }
+    }
+
+    /// Wait for the [Receiver] to be dropped or closed.
+    ///
+    /// This can be used to abandon computing a value which nobody is
+    /// interested in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::oneshot;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, rx) = oneshot::channel::<u32>();
+    ///
+    /// let value = tokio::select! {
+    ///     _ = tx.closed() => None,
+    ///     value = async move {
+    ///         drop(rx);
+    ///         std::future::pending::<u32>().await
+    ///     } => Some(value),
+    /// };
+    ///
+    /// assert_eq!(value, None);
+    /// # }
+    /// ```
+    pub async fn closed(&mut self) {
+        Closed { sender: self }.await
+    }
+
+    /// Poll for the [Receiver] to be dropped or closed.
+    ///
+    /// If the receiver is still open, the current task is registered to be
+    /// woken up once it's dropped or closed. This can be used to abandon computing a
+    /// value which nobody is interested in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::future::poll_fn;
+    /// use unsync::oneshot;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, rx) = oneshot::channel::<u32>();
+    ///
+    /// let ((), ()) = tokio::join!(poll_fn(|cx| tx.poll_closed(cx)), async move { drop(rx) });
+    /// # }
+    /// ```
+    pub fn poll_closed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+
+            if inner.is_closed(both_present) {
+                inner.closed = None;
+                return Poll::Ready(());
+            }
+
+            if !matches!(&inner.closed, Some(w) if w.will_wake(cx.waker())) {
+                inner.closed = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+/// Future associated with waiting through [Sender::closed].
+struct Closed<'a, T> {
+    sender: &'a mut Sender<T>,
+}
+
+impl<T> Future for Closed<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::into_inner(self).sender.poll_closed(cx)
+    }
 }
 
 /// Receiver end of the channel created through [channel].
 ///
-/// This implements [Future] so that it can be awaited or polled directly.
+/// This implements [Future] so that it can be awaited or polled directly. It
+/// resolves to [RecvError] if the [Sender] is dropped without sending a
+/// message.
 ///
 /// # Examples
 ///
@@ -110,15 +275,100 @@ impl<T> Sender<T> {
 /// # #[tokio::main(flavor = "current_thread")] async fn main() {
 /// let (tx, rx) = oneshot::channel();
 /// assert!(tx.send(1).is_ok());
-/// assert_eq!(rx.await, Some(1));
+/// assert_eq!(rx.await, Ok(1));
 /// # }
 /// ```
 pub struct Receiver<T> {
     inner: BiRc<Shared<T>>,
+    /// Indicates if the receiver has completed.
+    terminated: bool,
+}
+
+impl<T> Receiver<T> {
+    /// Close the channel without dropping the receiver.
+    ///
+    /// This prevents the [Sender] from sending a message and wakes up any
+    /// task waiting through [Sender::closed]. A message which was sent before
+    /// the channel was closed can still be received, for example through
+    /// [Receiver::try_recv].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::oneshot::{self, TryRecvError};
+    ///
+    /// let (tx, mut rx) = oneshot::channel();
+    /// assert!(tx.send(1).is_ok());
+    /// rx.close();
+    /// assert_eq!(rx.try_recv(), Ok(1));
+    ///
+    /// let (tx, mut rx) = oneshot::channel();
+    /// rx.close();
+    /// assert!(tx.is_closed());
+    /// assert_eq!(tx.send(2), Err(oneshot::SendError(2)));
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    /// ```
+    pub fn close(&mut self) {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.receiver_closed = true;
+
+            if let Some(waker) = inner.closed.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Try to receive the message without blocking.
+    ///
+    /// If this errors with [TryRecvError::Empty] the receiver is left
+    /// untouched, so it can still be awaited to receive the message later.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryRecvError::Empty] if no message has been sent yet, or
+    /// with [TryRecvError::Closed] if the [Sender] was dropped without sending
+    /// a message, the channel was closed through [Receiver::close] before a
+    /// message was sent, or the message has already been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::oneshot::{self, TryRecvError};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, mut rx) = oneshot::channel();
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// assert!(tx.send(1).is_ok());
+    /// assert_eq!(rx.await, Ok(1));
+    ///
+    /// let (tx, mut rx) = oneshot::channel();
+    /// assert!(tx.send(2).is_ok());
+    /// assert_eq!(rx.try_recv(), Ok(2));
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    /// # }
+    /// ```
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+
+            if let Some(value) = inner.buf.take() {
+                self.terminated = true;
+                return Ok(value);
+            }
+
+            if !both_present || inner.receiver_closed || self.terminated {
+                return Err(TryRecvError::Closed);
+            }
+
+            Err(TryRecvError::Empty)
+        }
+    }
 }
 
 impl<T> Future for Receiver<T> {
-    type Output = Option<T>;
+    type Output = Result<T, RecvError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         unsafe {
@@ -126,12 +376,14 @@ impl<T> Future for Receiver<T> {
             let (inner, both_present) = this.inner.get_mut_unchecked();
 
             if let Some(value) = inner.buf.take() {
-                return Poll::Ready(Some(value));
+                this.terminated = true;
+                return Poll::Ready(Ok(value));
             }
 
-            if !both_present {
+            if !both_present || inner.receiver_closed {
                 inner.waker = None;
-                return Poll::Ready(None);
+                this.terminated = true;
+                return Poll::Ready(Err(RecvError));
             }
 
             if !matches!(&inner.waker, Some(w) if w.will_wake(cx.waker())) {
@@ -143,6 +395,13 @@ impl<T> Future for Receiver<T> {
     }
 }
 
+#[cfg(feature = "futures-core")]
+impl<T> futures_core::FusedFuture for Receiver<T> {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
 impl<T> Drop for Sender<T> {
     fn drop(&mut self) {
         unsafe {
@@ -156,7 +415,10 @@ impl<T> Drop for Sender<T> {
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
         unsafe {
-            if let Some(waker) = self.inner.get_mut_unchecked().0.waker.take() {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.waker = None;
+
+            if let Some(waker) = inner.closed.take() {
                 waker.wake();
             }
         }
@@ -173,16 +435,22 @@ impl<T> Drop for Receiver<T> {
 /// # #[tokio::main(flavor = "current_thread")] async fn main() {
 /// let (tx, rx) = oneshot::channel();
 /// assert!(tx.send(1).is_ok());
-/// assert_eq!(rx.await, Some(1));
+/// assert_eq!(rx.await, Ok(1));
 /// # }
 /// ```
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
     let (a, b) = BiRc::new(Shared {
         waker: None,
+        closed: None,
         buf: None,
+        receiver_closed: false,
     });
 
-    let rx = Receiver { inner: a };
+    let rx = Receiver {
+        inner: a,
+        terminated: false,
+    };
+
     let tx = Sender { inner: b };
     (tx, rx)
 }
@@ -0,0 +1,433 @@
+//! An unsynchronized async reader-writer lock.
+//!
+//! This allows any number of readers or a single writer to hold the lock at
+//! the same time, across `.await` points in tasks running on the same thread.
+//!
+//! Tasks waiting for the lock acquire it in the order in which they started
+//! waiting. This means that the lock is *writer-preferring*: once a writer is
+//! waiting, readers which arrive after it have to wait until the writer has
+//! had its turn, so a steady stream of readers can't starve out writers.
+
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::fmt::{self, Display, Formatter};
+use core::future::Future;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::wait_list::WaitList;
+
+/// The kind of access a waiter is waiting for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+}
+
+/// An async reader-writer lock.
+///
+/// # Examples
+///
+/// ```
+/// use unsync::rwlock::RwLock;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let lock = RwLock::new(1);
+///
+/// {
+///     let a = lock.read().await;
+///     let b = lock.read().await;
+///     assert_eq!(*a + *b, 2);
+/// }
+///
+/// *lock.write().await += 1;
+/// assert_eq!(*lock.read().await, 2);
+/// # }
+/// ```
+pub struct RwLock<T: ?Sized> {
+    /// The number of readers currently holding the lock.
+    readers: Cell<usize>,
+    /// Indicates if a writer is currently holding the lock.
+    writer: Cell<bool>,
+    /// Tasks waiting to acquire the lock.
+    waiters: RefCell<WaitList<Access>>,
+    /// The guarded value.
+    value: UnsafeCell<T>,
+}
+
+/// Error raised by [RwLock::try_read] or [RwLock::try_write] if the lock
+/// couldn't be acquired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TryLockError;
+
+impl Display for TryLockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "rwlock is locked")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryLockError {}
+
+impl<T> RwLock<T> {
+    /// Construct a new unlocked reader-writer lock guarding the given value.
+    pub fn new(value: T) -> Self {
+        Self {
+            readers: Cell::new(0),
+            writer: Cell::new(false),
+            waiters: RefCell::new(WaitList::new()),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the lock, returning the guarded value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Lock for reading, waiting until no writer holds or is waiting for the
+    /// lock ahead of us.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use tokio::task;
+    /// use unsync::rwlock::RwLock;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+    /// let lock = Rc::new(RwLock::new(0));
+    ///
+    /// let local = task::LocalSet::new();
+    ///
+    /// let value = local.run_until(async {
+    ///     let mut guard = lock.write().await;
+    ///
+    ///     let reader = task::spawn_local({
+    ///         let lock = lock.clone();
+    ///         async move { *lock.read().await }
+    ///     });
+    ///
+    ///     task::yield_now().await;
+    ///     *guard = 42;
+    ///     drop(guard);
+    ///
+    ///     reader.await
+    /// }).await?;
+    ///
+    /// assert_eq!(value, 42);
+    /// # Ok(()) }
+    /// ```
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        Acquire {
+            lock: self,
+            access: Access::Read,
+            key: None,
+        }
+        .await;
+
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Lock for writing, waiting until no other reader or writer holds the
+    /// lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::rwlock::RwLock;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let lock = RwLock::new(vec![1]);
+    /// lock.write().await.push(2);
+    /// assert_eq!(*lock.read().await, vec![1, 2]);
+    /// # }
+    /// ```
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        Acquire {
+            lock: self,
+            access: Access::Write,
+            key: None,
+        }
+        .await;
+
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Try to lock for reading without waiting.
+    ///
+    /// To preserve fairness this fails if other tasks are waiting for the
+    /// lock.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryLockError] if the lock couldn't be acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::rwlock::RwLock;
+    ///
+    /// let lock = RwLock::new(1);
+    ///
+    /// let a = lock.try_read().unwrap();
+    /// let b = lock.try_read().unwrap();
+    /// assert!(lock.try_write().is_err());
+    /// ```
+    pub fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, TryLockError> {
+        if !self.waiters.borrow().is_empty() || !self.try_acquire(Access::Read) {
+            return Err(TryLockError);
+        }
+
+        Ok(RwLockReadGuard { lock: self })
+    }
+
+    /// Try to lock for writing without waiting.
+    ///
+    /// To preserve fairness this fails if other tasks are waiting for the
+    /// lock.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryLockError] if the lock couldn't be acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::rwlock::RwLock;
+    ///
+    /// let lock = RwLock::new(1);
+    ///
+    /// let guard = lock.try_write().unwrap();
+    /// assert!(lock.try_read().is_err());
+    /// drop(guard);
+    ///
+    /// assert!(lock.try_read().is_ok());
+    /// ```
+    pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, T>, TryLockError> {
+        if !self.waiters.borrow().is_empty() || !self.try_acquire(Access::Write) {
+            return Err(TryLockError);
+        }
+
+        Ok(RwLockWriteGuard { lock: self })
+    }
+
+    /// Get a mutable reference to the guarded value.
+    ///
+    /// No locking is necessary since this requires exclusive access to the
+    /// lock.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Try to acquire the lock with the given access, ignoring any waiters.
+    fn try_acquire(&self, access: Access) -> bool {
+        if self.writer.get() {
+            return false;
+        }
+
+        match access {
+            Access::Read => {
+                self.readers.set(self.readers.get() + 1);
+            }
+            Access::Write => {
+                if self.readers.get() != 0 {
+                    return false;
+                }
+
+                self.writer.set(true);
+            }
+        }
+
+        true
+    }
+
+    /// Wake the next waiter in line if it's able to acquire the lock.
+    fn wake_next(&self, waiters: &WaitList<Access>) {
+        let ready = match waiters.front() {
+            Some(Access::Read) => !self.writer.get(),
+            Some(Access::Write) => !self.writer.get() && self.readers.get() == 0,
+            None => false,
+        };
+
+        if ready {
+            waiters.wake_front();
+        }
+    }
+
+    /// Release a read lock.
+    fn release_read(&self) {
+        self.readers.set(self.readers.get() - 1);
+        self.wake_next(&self.waiters.borrow());
+    }
+
+    /// Release a write lock.
+    fn release_write(&self) {
+        self.writer.set(false);
+        self.wake_next(&self.waiters.borrow());
+    }
+}
+
+impl<T> Default for RwLock<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Future associated with acquiring the lock.
+struct Acquire<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+    access: Access,
+    /// Our key in the wait list, if we are waiting for the lock.
+    key: Option<usize>,
+}
+
+impl<T: ?Sized> Future for Acquire<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let lock = this.lock;
+        let mut waiters = lock.waiters.borrow_mut();
+
+        // Only the task at the front of the queue is allowed to acquire the
+        // lock, anyone else has to wait for their turn.
+        let our_turn = match this.key {
+            Some(key) => waiters.is_front(key),
+            None => waiters.is_empty(),
+        };
+
+        if our_turn && lock.try_acquire(this.access) {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            // Readers queued up behind us can acquire the lock as well.
+            lock.wake_next(&waiters);
+            return Poll::Ready(());
+        }
+
+        match this.key {
+            Some(key) => waiters.register(key, cx.waker()),
+            None => this.key = Some(waiters.push_back(this.access, cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T: ?Sized> Drop for Acquire<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut waiters = self.lock.waiters.borrow_mut();
+            waiters.remove(key);
+
+            // We might've been woken up to acquire the lock, or been the writer
+            // holding back readers, so make sure it's passed on.
+            self.lock.wake_next(&waiters);
+        }
+    }
+}
+
+/// A guard returned by [RwLock::read] and [RwLock::try_read].
+///
+/// The read lock is released when the guard is dropped.
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: there are no writers while a read guard is held.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release_read();
+    }
+}
+
+/// A guard returned by [RwLock::write] and [RwLock::try_write].
+///
+/// The write lock is released when the guard is dropped.
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    /// Atomically downgrade a write lock into a read lock, without letting any
+    /// other writer acquire the lock in between.
+    ///
+    /// Readers waiting at the front of the queue are allowed to acquire the
+    /// lock as well once it's downgraded.
+    ///
+    /// This is an associated function which needs to be called as
+    /// `RwLockWriteGuard::downgrade(guard)`, so that it doesn't conflict with
+    /// methods on the guarded value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::rwlock::{RwLock, RwLockWriteGuard};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let lock = RwLock::new(1);
+    ///
+    /// let mut guard = lock.write().await;
+    /// *guard += 1;
+    ///
+    /// let guard = RwLockWriteGuard::downgrade(guard);
+    /// assert_eq!(*guard, 2);
+    /// assert_eq!(*lock.try_read().unwrap(), 2);
+    /// assert!(lock.try_write().is_err());
+    /// # }
+    /// ```
+    pub fn downgrade(this: Self) -> RwLockReadGuard<'a, T> {
+        let this = ManuallyDrop::new(this);
+        let lock = this.lock;
+
+        lock.writer.set(false);
+        lock.readers.set(lock.readers.get() + 1);
+        lock.wake_next(&lock.waiters.borrow());
+
+        RwLockReadGuard { lock }
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: the write guard has exclusive access to the value.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: the write guard has exclusive access to the value.
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release_write();
+    }
+}
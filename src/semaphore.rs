@@ -0,0 +1,473 @@
+//! An unsynchronized async counting semaphore.
+//!
+//! This can be used to limit how many tasks running on the same thread are
+//! allowed to access a resource at the same time.
+//!
+//! Tasks waiting for permits acquire them in the order in which they started
+//! waiting. A task which is waiting for a large number of permits holds back
+//! the tasks behind it, even if they only need a few permits which are
+//! available, so that large acquires can't be starved out by small ones.
+
+use alloc::rc::Rc;
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Display, Formatter};
+use core::future::Future;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::wait_list::WaitList;
+
+/// Error raised when acquiring permits from a [Semaphore] which has been
+/// closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AcquireError;
+
+impl Display for AcquireError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "semaphore closed")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AcquireError {}
+
+/// Error raised when trying to acquire permits from a [Semaphore] without
+/// waiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TryAcquireError {
+    /// The semaphore has been closed.
+    Closed,
+    /// There are not enough permits available, or other tasks are waiting
+    /// ahead of us.
+    NoPermits,
+}
+
+impl Display for TryAcquireError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TryAcquireError::Closed => write!(f, "semaphore closed"),
+            TryAcquireError::NoPermits => write!(f, "no permits available"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryAcquireError {}
+
+/// An async counting semaphore.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use tokio::task;
+/// use unsync::semaphore::Semaphore;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+/// let semaphore = Rc::new(Semaphore::new(2));
+/// let active = Rc::new(Cell::new(0));
+///
+/// let local = task::LocalSet::new();
+///
+/// local.run_until(async {
+///     let mut tasks = Vec::new();
+///
+///     for _ in 0..8 {
+///         let semaphore = semaphore.clone();
+///         let active = active.clone();
+///
+///         tasks.push(task::spawn_local(async move {
+///             let _permit = semaphore.acquire(1).await.unwrap();
+///             active.set(active.get() + 1);
+///             assert!(active.get() <= 2);
+///             task::yield_now().await;
+///             active.set(active.get() - 1);
+///         }));
+///     }
+///
+///     for t in tasks {
+///         t.await?;
+///     }
+///
+///     Ok::<_, task::JoinError>(())
+/// }).await?;
+/// # Ok(()) }
+/// ```
+pub struct Semaphore {
+    /// The number of available permits.
+    permits: Cell<usize>,
+    /// Indicates if the semaphore has been closed.
+    closed: Cell<bool>,
+    /// Tasks waiting for permits, along with the number of permits they're
+    /// waiting for.
+    waiters: RefCell<WaitList<usize>>,
+}
+
+impl Semaphore {
+    /// Construct a new semaphore with the given number of permits.
+    pub fn new(permits: usize) -> Self {
+        Self {
+            permits: Cell::new(permits),
+            closed: Cell::new(false),
+            waiters: RefCell::new(WaitList::new()),
+        }
+    }
+
+    /// Get the number of permits which are currently available.
+    pub fn available_permits(&self) -> usize {
+        self.permits.get()
+    }
+
+    /// Add `n` new permits to the semaphore, waking up waiting tasks which
+    /// can now make progress.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of available permits would overflow `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::semaphore::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(0);
+    /// assert!(semaphore.try_acquire(1).is_err());
+    ///
+    /// semaphore.add_permits(2);
+    /// assert!(semaphore.try_acquire(2).is_ok());
+    /// ```
+    pub fn add_permits(&self, n: usize) {
+        let permits = self
+            .permits
+            .get()
+            .checked_add(n)
+            .expect("number of added permits would overflow the available permits");
+        self.permits.set(permits);
+        self.wake_next(&self.waiters.borrow());
+    }
+
+    /// Close the semaphore.
+    ///
+    /// All tasks waiting for permits are woken up and receive an
+    /// [AcquireError], as do any later attempts at acquiring permits. Permits
+    /// which have already been acquired are unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::semaphore::{AcquireError, Semaphore, TryAcquireError};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let semaphore = Semaphore::new(1);
+    ///
+    /// let _permit = semaphore.acquire(1).await.unwrap();
+    /// let (_, result) = tokio::join!(async { semaphore.close() }, semaphore.acquire(1));
+    ///
+    /// assert_eq!(result.err(), Some(AcquireError));
+    /// assert_eq!(semaphore.try_acquire(1).err(), Some(TryAcquireError::Closed));
+    /// # }
+    /// ```
+    pub fn close(&self) {
+        self.closed.set(true);
+        self.waiters.borrow_mut().wake_all();
+    }
+
+    /// Test if the semaphore has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+
+    /// Acquire `n` permits, waiting until they become available.
+    ///
+    /// Tasks waiting for permits acquire them in the order in which they
+    /// started waiting.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [AcquireError] if the semaphore has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::semaphore::Semaphore;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let semaphore = Semaphore::new(3);
+    ///
+    /// let permit = semaphore.acquire(2).await.unwrap();
+    /// assert_eq!(permit.num_permits(), 2);
+    /// assert_eq!(semaphore.available_permits(), 1);
+    ///
+    /// drop(permit);
+    /// assert_eq!(semaphore.available_permits(), 3);
+    /// # }
+    /// ```
+    pub async fn acquire(&self, n: usize) -> Result<SemaphorePermit<'_>, AcquireError> {
+        Acquire {
+            semaphore: self,
+            n,
+            key: None,
+        }
+        .await?;
+
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Try to acquire `n` permits without waiting.
+    ///
+    /// To preserve fairness this fails if other tasks are waiting for permits.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryAcquireError::Closed] if the semaphore has been closed,
+    /// or [TryAcquireError::NoPermits] if the permits couldn't be acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::semaphore::{Semaphore, TryAcquireError};
+    ///
+    /// let semaphore = Semaphore::new(2);
+    ///
+    /// let _permit = semaphore.try_acquire(2).unwrap();
+    /// assert_eq!(semaphore.try_acquire(1).err(), Some(TryAcquireError::NoPermits));
+    /// ```
+    pub fn try_acquire(&self, n: usize) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_acquire_inner(n)?;
+
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Acquire `n` permits through an [Rc], returning a permit which keeps the
+    /// semaphore alive and isn't bound to a lifetime.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [AcquireError] if the semaphore has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use tokio::task;
+    /// use unsync::semaphore::Semaphore;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+    /// let semaphore = Rc::new(Semaphore::new(1));
+    ///
+    /// let local = task::LocalSet::new();
+    ///
+    /// local.run_until(async {
+    ///     let permit = semaphore.clone().acquire_owned(1).await.unwrap();
+    ///
+    ///     // The permit is released once the spawned task completes.
+    ///     let task = task::spawn_local(async move {
+    ///         let _permit = permit;
+    ///         task::yield_now().await;
+    ///     });
+    ///
+    ///     assert_eq!(semaphore.available_permits(), 0);
+    ///     task.await
+    /// }).await?;
+    ///
+    /// assert_eq!(semaphore.available_permits(), 1);
+    /// # Ok(()) }
+    /// ```
+    pub async fn acquire_owned(
+        self: Rc<Self>,
+        n: usize,
+    ) -> Result<OwnedSemaphorePermit, AcquireError> {
+        Acquire {
+            semaphore: &self,
+            n,
+            key: None,
+        }
+        .await?;
+
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Try to acquire `n` permits through an [Rc] without waiting.
+    ///
+    /// # Errors
+    ///
+    /// See [Semaphore::try_acquire].
+    pub fn try_acquire_owned(
+        self: Rc<Self>,
+        n: usize,
+    ) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.try_acquire_inner(n)?;
+
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Try to take `n` permits, respecting any waiters.
+    fn try_acquire_inner(&self, n: usize) -> Result<(), TryAcquireError> {
+        if self.closed.get() {
+            return Err(TryAcquireError::Closed);
+        }
+
+        if !self.waiters.borrow().is_empty() || self.permits.get() < n {
+            return Err(TryAcquireError::NoPermits);
+        }
+
+        self.permits.set(self.permits.get() - n);
+        Ok(())
+    }
+
+    /// Wake the next waiter in line if enough permits are available for it.
+    fn wake_next(&self, waiters: &WaitList<usize>) {
+        if matches!(waiters.front(), Some(&n) if n <= self.permits.get()) {
+            waiters.wake_front();
+        }
+    }
+
+    /// Return permits to the semaphore.
+    fn release(&self, n: usize) {
+        if n != 0 {
+            self.add_permits(n);
+        }
+    }
+}
+
+/// Future associated with acquiring permits.
+struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    /// The number of permits to acquire.
+    n: usize,
+    /// Our key in the wait list, if we are waiting for permits.
+    key: Option<usize>,
+}
+
+impl Future for Acquire<'_> {
+    type Output = Result<(), AcquireError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let semaphore = this.semaphore;
+        let mut waiters = semaphore.waiters.borrow_mut();
+
+        if semaphore.closed.get() {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            return Poll::Ready(Err(AcquireError));
+        }
+
+        // Only the task at the front of the queue is allowed to acquire
+        // permits, anyone else has to wait for their turn.
+        let our_turn = match this.key {
+            Some(key) => waiters.is_front(key),
+            None => waiters.is_empty(),
+        };
+
+        if our_turn && this.n <= semaphore.permits.get() {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            semaphore.permits.set(semaphore.permits.get() - this.n);
+
+            // Pass on any remaining permits to the next task in line.
+            semaphore.wake_next(&waiters);
+            return Poll::Ready(Ok(()));
+        }
+
+        match this.key {
+            Some(key) => waiters.register(key, cx.waker()),
+            None => this.key = Some(waiters.push_back(this.n, cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut waiters = self.semaphore.waiters.borrow_mut();
+            waiters.remove(key);
+
+            // We might've been holding back tasks behind us, so make sure they
+            // get a chance to acquire permits.
+            self.semaphore.wake_next(&waiters);
+        }
+    }
+}
+
+/// Permits acquired through [Semaphore::acquire] or
+/// [Semaphore::try_acquire].
+///
+/// The permits are returned to the semaphore when this is dropped.
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    /// Get the number of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Forget the permits without returning them to the semaphore, reducing
+    /// the number of available permits permanently.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.release(mem::take(&mut self.permits));
+    }
+}
+
+/// Owned permits acquired through [Semaphore::acquire_owned] or
+/// [Semaphore::try_acquire_owned].
+///
+/// This keeps the [Semaphore] alive, so it's not bound to a lifetime. The
+/// permits are returned to the semaphore when this is dropped.
+pub struct OwnedSemaphorePermit {
+    semaphore: Rc<Semaphore>,
+    permits: usize,
+}
+
+impl OwnedSemaphorePermit {
+    /// Get the number of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Forget the permits without returning them to the semaphore, reducing
+    /// the number of available permits permanently.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// Get the [Semaphore] that the permits were acquired from.
+    pub fn semaphore(&self) -> &Rc<Semaphore> {
+        &self.semaphore
+    }
+}
+
+impl Drop for OwnedSemaphorePermit {
+    fn drop(&mut self) {
+        self.semaphore.release(mem::take(&mut self.permits));
+    }
+}
@@ -6,12 +6,14 @@
 //! This allocates storage internally to maintain shared state between the
 //! [Sender] and [Receiver].
 
-use std::collections::VecDeque;
-use std::error::Error;
-use std::fmt::{self, Debug, Display, Formatter};
-use std::future::Future;
-use std::pin::Pin;
-use std::task::{Context, Poll, Waker};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::future::Future;
+use core::mem;
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
 
 use crate::bi_rc::BiRc;
 
@@ -19,13 +21,87 @@ use crate::bi_rc::BiRc;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SendError<T>(pub T);
 
+impl<T> SendError<T> {
+    /// Get the value which couldn't be sent.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
 impl<T> Display for SendError<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "channel disconnected")
     }
 }
 
-impl<T> Error for SendError<T> where T: Debug {}
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SendError<T> where T: fmt::Debug {}
+
+/// Error raised when trying to send a message over the queue without blocking.
+///
+/// # Examples
+///
+/// ```
+/// use unsync::spsc::{self, TrySendError};
+///
+/// let (mut tx, rx) = spsc::channel(1);
+/// assert!(tx.try_send(1).is_ok());
+/// assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+///
+/// drop(rx);
+/// assert_eq!(tx.try_send(3), Err(TrySendError::Closed(3)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrySendError<T> {
+    /// The channel is at capacity.
+    Full(T),
+    /// The [Receiver] has been dropped.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Get the value which couldn't be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) => value,
+            TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> Display for TrySendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => write!(f, "channel full"),
+            TrySendError::Closed(..) => write!(f, "channel disconnected"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for TrySendError<T> where T: fmt::Debug {}
+
+/// Error raised when trying to receive a message over the queue without
+/// blocking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TryRecvError {
+    /// There are no messages in the queue.
+    Empty,
+    /// The queue is empty and the [Sender] has been dropped.
+    Closed,
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "channel empty"),
+            TryRecvError::Closed => write!(f, "channel disconnected"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryRecvError {}
 
 /// Interior shared state.
 ///
@@ -38,16 +114,56 @@ struct Shared<T> {
     tx: Option<Waker>,
     /// Waker to wake once receiving is available.
     rx: Option<Waker>,
+    /// Waker to wake once the receiver is dropped or closed.
+    closed: Option<Waker>,
+    /// Indicates if the receiver has closed the channel through
+    /// [Receiver::close].
+    receiver_closed: bool,
     /// Test if the interior value is set.
     buf: VecDeque<T>,
-    /// Indicates if the channel is unbounded.
-    unbounded: bool,
+    /// The capacity of the channel, or `None` if it's unbounded.
+    capacity: Option<NonZeroUsize>,
+    /// Set once the sender has been closed as a sink.
+    sender_closed: bool,
+    /// The number of slots held by outstanding permits.
+    reserved: usize,
 }
 
 impl<T> Shared<T> {
     /// Test if the current channel is at capacity.
     fn at_capacity(&self) -> bool {
-        !self.unbounded && self.buf.capacity() == self.buf.len()
+        !self.has_capacity(1)
+    }
+
+    /// Release `n` reserved slots.
+    fn release(&mut self, n: usize) {
+        self.reserved -= n;
+
+        // A closed receiver might be waiting for the last permit to go away.
+        if self.receiver_closed && self.reserved == 0 {
+            if let Some(waker) = &self.rx {
+                waker.wake_by_ref();
+            }
+        }
+    }
+
+    /// Test if no more messages can be sent, because the receiver has been
+    /// dropped or closed, or the sender has been closed as a sink.
+    fn is_closed(&self, both_present: bool) -> bool {
+        !both_present || self.receiver_closed || self.sender_closed
+    }
+
+    /// Test if no more messages can be received once the buffer is empty.
+    fn is_drained(&self, both_present: bool) -> bool {
+        !both_present || self.sender_closed || (self.receiver_closed && self.reserved == 0)
+    }
+
+    /// Test if there is capacity to hold `n` additional messages.
+    fn has_capacity(&self, n: usize) -> bool {
+        match self.capacity {
+            Some(capacity) => self.buf.len() + self.reserved + n <= capacity.get(),
+            None => true,
+        }
     }
 }
 
@@ -62,6 +178,11 @@ impl<T> Sender<T> {
     /// This will succeed if there is sufficient capacity to send, but fail
     /// otherwise.
     ///
+    /// # Errors
+    ///
+    /// Errors with [TrySendError::Full] if the channel is at capacity, or with
+    /// [TrySendError::Closed] if the [Receiver] has been dropped.
+    ///
     /// Note: don't attempt to use this as an optimization over [Sender::send]
     /// since it already performs this operation internally as needed.
     ///
@@ -94,12 +215,16 @@ impl<T> Sender<T> {
     /// assert_eq!(collected, vec![2, 3, 5]);
     /// # }
     /// ```
-    pub fn try_send(&mut self, value: T) -> Result<(), SendError<T>> {
+    pub fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
         unsafe {
             let (inner, both_present) = self.inner.get_mut_unchecked();
 
-            if !both_present || inner.at_capacity() {
-                return Err(SendError(value));
+            if inner.is_closed(both_present) {
+                return Err(TrySendError::Closed(value));
+            }
+
+            if inner.at_capacity() {
+                return Err(TrySendError::Full(value));
             }
 
             inner.buf.push_back(value);
@@ -147,34 +272,142 @@ impl<T> Sender<T> {
     /// assert_eq!(collected, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
     /// # Ok(()) }
     /// ```
-    pub async fn send(&mut self, value: T) -> Result<(), SendError<T>> {
-        Send {
-            inner: &self.inner,
+    pub fn send(&mut self, value: T) -> SendFut<'_, T> {
+        SendFut {
+            sender: self,
             value: Some(value),
         }
-        .await
     }
-}
 
-/// Future returned when sending a value through [Sender::send].
-struct Send<'a, T> {
-    inner: &'a BiRc<Shared<T>>,
-    value: Option<T>,
-}
+    /// Send every message produced by an iterator on this channel.
+    ///
+    /// This fills up all available capacity before waking the [Receiver]
+    /// once, and then waits for more capacity to send the rest.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SendError] containing the first message which couldn't be
+    /// sent if the [Receiver] has been dropped or closed. Any messages left in
+    /// the iterator are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(4);
+    ///
+    /// let (result, received) = tokio::join!(
+    ///     async move { tx.send_all(0..10).await },
+    ///     async move {
+    ///         let mut received = Vec::new();
+    ///
+    ///         while let Some(value) = rx.recv().await {
+    ///             received.push(value);
+    ///         }
+    ///
+    ///         received
+    ///     },
+    /// );
+    ///
+    /// assert!(result.is_ok());
+    /// assert_eq!(received, (0..10).collect::<Vec<_>>());
+    /// # }
+    /// ```
+    pub async fn send_all<I>(&mut self, iter: I) -> Result<(), SendError<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        SendAll {
+            inner: &self.inner,
+            iter: iter.into_iter(),
+            pending: None,
+        }
+        .await
+    }
 
-impl<'a, T> Future for Send<'a, T> {
-    type Output = Result<(), SendError<T>>;
+    /// Send a slice of messages on this channel by copying them.
+    ///
+    /// This behaves like [Sender::send_all], but copies as many messages as
+    /// there is capacity for at once.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SendError] containing the messages which weren't sent if
+    /// the [Receiver] has been dropped or closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(4);
+    /// let values = [1u8, 2, 3, 4, 5, 6];
+    ///
+    /// let (result, ()) = tokio::join!(tx.send_slice(&values), async {
+    ///     let mut buf = [0; 4];
+    ///     assert_eq!(rx.recv_into(&mut buf).await, 4);
+    ///     assert_eq!(buf, [1, 2, 3, 4]);
+    /// });
+    ///
+    /// assert!(result.is_ok());
+    /// assert_eq!(rx.recv().await, Some(5));
+    /// assert_eq!(rx.recv().await, Some(6));
+    ///
+    /// rx.close();
+    /// assert_eq!(tx.send_slice(&values).await, Err(unsync::spsc::SendError(&values[..])));
+    /// # }
+    /// ```
+    pub async fn send_slice<'b>(&mut self, values: &'b [T]) -> Result<(), SendError<&'b [T]>>
+    where
+        T: Copy,
+    {
+        SendSlice {
+            inner: &self.inner,
+            values,
+        }
+        .await
+    }
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    /// Poll for capacity to send a message on this channel.
+    ///
+    /// Once this returns `Poll::Ready(Ok(()))` the next call to
+    /// [Sender::start_send] is guaranteed to have capacity to send. If the
+    /// channel is at capacity the current task is registered to be woken up
+    /// once the receiver has made room.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SendError] if the [Receiver] has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::future::poll_fn;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(1);
+    ///
+    /// assert!(poll_fn(|cx| tx.poll_ready(cx)).await.is_ok());
+    /// assert!(tx.start_send(1).is_ok());
+    ///
+    /// let ((), value) = tokio::join!(
+    ///     async {
+    ///         assert!(poll_fn(|cx| tx.poll_ready(cx)).await.is_ok());
+    ///         assert!(tx.start_send(2).is_ok());
+    ///     },
+    ///     rx.recv(),
+    /// );
+    ///
+    /// assert_eq!(value, Some(1));
+    /// assert_eq!(rx.recv().await, Some(2));
+    /// # }
+    /// ```
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
         unsafe {
-            let this = Pin::get_unchecked_mut(self);
-
-            let (inner, both_present) = this.inner.get_mut_unchecked();
+            let (inner, both_present) = self.inner.get_mut_unchecked();
 
-            if !both_present {
+            if inner.is_closed(both_present) {
                 inner.tx = None;
-                let value = this.value.take().expect("future already completed");
-                return Poll::Ready(Err(SendError(value)));
+                return Poll::Ready(Err(SendError(())));
             }
 
             // If we are at capacity, register ourselves as an interested waker
@@ -187,113 +420,962 @@ impl<'a, T> Future for Send<'a, T> {
                 return Poll::Pending;
             };
 
-            inner
-                .buf
-                .push_back(this.value.take().expect("future already completed"));
+            Poll::Ready(Ok(()))
+        }
+    }
 
-            if let Some(waker) = &inner.rx {
-                waker.wake_by_ref();
-            };
+    /// Send a message on this channel after capacity has been reserved
+    /// through [Sender::poll_ready].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TrySendError::Closed] if the [Receiver] has been dropped,
+    /// or with [TrySendError::Full] if the channel is at capacity because
+    /// [Sender::poll_ready] wasn't used to wait for capacity first.
+    pub fn start_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        self.try_send(value)
+    }
 
-            Poll::Ready(Ok(()))
+    /// Test if the [Receiver] has been dropped or closed, or the sender has
+    /// been closed as a sink, in which case sending will fail.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let (tx, rx) = unsync::spsc::channel::<u32>(1);
+    /// assert!(!tx.is_closed());
+    /// drop(rx);
+    /// assert!(tx.is_closed());
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+            inner.is_closed(both_present)
         }
     }
-}
 
-/// Receiver end of the channel created through [channel].
-pub struct Receiver<T> {
-    inner: BiRc<Shared<T>>,
-}
+    /// Take the messages which were sent but never received, once the
+    /// [Receiver] has been dropped.
+    ///
+    /// This can be used to reroute work which was accepted by a consumer that
+    /// has since gone away. If the receiver is still alive, nothing is taken
+    /// and an empty vector is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let (mut tx, mut rx) = unsync::spsc::channel(4);
+    ///
+    /// assert!(tx.try_send(1).is_ok());
+    /// assert!(tx.try_send(2).is_ok());
+    /// assert!(tx.take_undelivered().is_empty());
+    ///
+    /// drop(rx);
+    /// assert_eq!(tx.take_undelivered(), vec![1, 2]);
+    /// assert!(tx.take_undelivered().is_empty());
+    /// ```
+    pub fn take_undelivered(&mut self) -> Vec<T> {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
 
-impl<T> Receiver<T> {
-    /// Receive a message on this channel.
+            if both_present {
+                return Vec::new();
+            }
+
+            inner.buf.drain(..).collect()
+        }
+    }
+
+    /// Wait for the [Receiver] to be dropped or closed.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use tokio::task;
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, rx) = unsync::spsc::channel::<u32>(1);
     ///
-    /// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+    /// let ((), ()) = tokio::join!(tx.closed(), async move { drop(rx) });
+    /// assert!(tx.is_closed());
+    /// # }
+    /// ```
+    pub async fn closed(&mut self) {
+        Closed { sender: self }.await
+    }
+
+    /// Poll for the [Receiver] to be dropped or closed.
+    ///
+    /// If the receiver is still open, the current task is registered to be
+    /// woken up once it's dropped or closed.
+    pub fn poll_closed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+
+            if inner.is_closed(both_present) {
+                inner.closed = None;
+                return Poll::Ready(());
+            }
+
+            if !matches!(&inner.closed, Some(w) if w.will_wake(cx.waker())) {
+                inner.closed = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        }
+    }
+
+    /// Wait for capacity to send a message on this channel, and reserve it.
+    ///
+    /// The reserved slot is held until the returned [Permit] is used to send a
+    /// message or is dropped. This allows for checking that there is room
+    /// before constructing a message which is expensive to build.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SendError] if the [Receiver] has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
     /// let (mut tx, mut rx) = unsync::spsc::channel(1);
     ///
-    /// let local = task::LocalSet::new();
+    /// let permit = tx.reserve().await.unwrap();
+    /// permit.send(1);
+    /// assert!(tx.try_send(2).is_err());
     ///
-    /// let collected = local.run_until(async move {
-    ///     let collect = task::spawn_local(async move {
-    ///         let mut out = Vec::new();
+    /// assert_eq!(rx.recv().await, Some(1));
     ///
-    ///         while let Some(value) = rx.recv().await {
-    ///             out.push(value);
-    ///         }
+    /// // Dropping the permit releases the slot.
+    /// let permit = tx.reserve().await.unwrap();
+    /// drop(permit);
+    /// assert!(tx.try_send(2).is_ok());
     ///
-    ///         out
-    ///     });
+    /// drop(tx);
+    /// assert_eq!(rx.recv().await, Some(2));
+    /// assert_eq!(rx.recv().await, None);
+    /// # }
+    /// ```
+    pub async fn reserve(&mut self) -> Result<Permit<'_, T>, SendError<()>> {
+        Reserve {
+            inner: &self.inner,
+            n: 1,
+        }
+        .await?;
+
+        Ok(Permit { inner: &self.inner })
+    }
+
+    /// Wait for capacity to send `n` messages on this channel, and reserve
+    /// all of them at once.
     ///
-    ///     let sender = task::spawn_local(async move {
-    ///         for n in 0..10 {
-    ///             let result = tx.send(n).await;
-    ///         }
-    ///     });
+    /// The returned [PermitIterator] yields one [Permit] for every reserved
+    /// slot. Slots which haven't been used are released when it's dropped.
     ///
-    ///     collect.await
-    /// }).await?;
+    /// # Errors
     ///
-    /// assert_eq!(collected, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-    /// # Ok(()) }
+    /// Errors with [SendError] if the [Receiver] has been dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the capacity of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(4);
+    ///
+    /// let permits = tx.reserve_many(3).await.unwrap();
+    /// assert_eq!(permits.len(), 3);
+    ///
+    /// for (permit, value) in permits.zip([1, 2]) {
+    ///     permit.send(value);
+    /// }
+    ///
+    /// assert!(tx.try_send(3).is_ok());
+    /// drop(tx);
+    ///
+    /// assert_eq!(rx.recv().await, Some(1));
+    /// assert_eq!(rx.recv().await, Some(2));
+    /// assert_eq!(rx.recv().await, Some(3));
+    /// assert_eq!(rx.recv().await, None);
+    /// # }
     /// ```
-    pub async fn recv(&mut self) -> Option<T> {
-        Recv { inner: &self.inner }.await
+    pub async fn reserve_many(&mut self, n: usize) -> Result<PermitIterator<'_, T>, SendError<()>> {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            assert!(
+                !matches!(inner.capacity, Some(capacity) if n > capacity.get()),
+                "cannot reserve more than the capacity of the channel"
+            );
+        }
+
+        Reserve {
+            inner: &self.inner,
+            n,
+        }
+        .await?;
+
+        Ok(PermitIterator {
+            inner: &self.inner,
+            n,
+        })
     }
 }
 
-struct Recv<'a, T> {
+/// Future associated with sending through [Sender::send_all].
+struct SendAll<'a, T, I> {
     inner: &'a BiRc<Shared<T>>,
+    iter: I,
+    /// A message taken from the iterator which is waiting for capacity.
+    pending: Option<T>,
 }
 
-impl<'a, T> Future for Recv<'a, T> {
-    type Output = Option<T>;
+// Neither the iterator nor its messages are ever pinned.
+impl<T, I> Unpin for SendAll<'_, T, I> {}
+
+impl<T, I> Future for SendAll<'_, T, I>
+where
+    I: Iterator<Item = T>,
+{
+    type Output = Result<(), SendError<T>>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+
         unsafe {
-            let this = Pin::get_unchecked_mut(self);
             let (inner, both_present) = this.inner.get_mut_unchecked();
+            let mut sent = false;
 
-            if let Some(value) = inner.buf.pop_front() {
-                return Poll::Ready(Some(value));
-            }
+            let result = loop {
+                let value = match this.pending.take().or_else(|| this.iter.next()) {
+                    Some(value) => value,
+                    None => break Poll::Ready(Ok(())),
+                };
 
-            if !both_present {
-                inner.rx = None;
-                return Poll::Ready(None);
-            }
+                if inner.is_closed(both_present) {
+                    inner.tx = None;
+                    break Poll::Ready(Err(SendError(value)));
+                }
 
-            if !matches!(&inner.rx, Some(w) if !w.will_wake(cx.waker())) {
-                inner.rx = Some(cx.waker().clone())
-            }
+                if inner.at_capacity() {
+                    this.pending = Some(value);
 
-            if let Some(tx) = &inner.tx {
-                tx.wake_by_ref();
+                    if !matches!(&inner.tx, Some(w) if w.will_wake(cx.waker())) {
+                        inner.tx = Some(cx.waker().clone());
+                    }
+
+                    break Poll::Pending;
+                }
+
+                inner.buf.push_back(value);
+                sent = true;
+            };
+
+            // Wake the receiver once for everything that was sent.
+            if sent {
+                if let Some(waker) = &inner.rx {
+                    waker.wake_by_ref();
+                }
             }
 
-            Poll::Pending
+            result
         }
     }
 }
 
-impl<T> Drop for Sender<T> {
-    fn drop(&mut self) {
-        unsafe {
-            if let Some(waker) = self.inner.get_mut_unchecked().0.rx.take() {
-                waker.wake();
-            }
-        }
-    }
+/// Future associated with sending through [Sender::send_slice].
+struct SendSlice<'a, 'b, T> {
+    inner: &'a BiRc<Shared<T>>,
+    /// The messages which have yet to be sent.
+    values: &'b [T],
 }
 
-impl<T> Drop for Receiver<T> {
-    fn drop(&mut self) {
-        unsafe {
-            if let Some(waker) = self.inner.get_mut_unchecked().0.tx.take() {
+impl<'b, T> Future for SendSlice<'_, 'b, T>
+where
+    T: Copy,
+{
+    type Output = Result<(), SendError<&'b [T]>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+
+        unsafe {
+            let (inner, both_present) = this.inner.get_mut_unchecked();
+
+            if this.values.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            if inner.is_closed(both_present) {
+                inner.tx = None;
+                return Poll::Ready(Err(SendError(this.values)));
+            }
+
+            let room = match inner.capacity {
+                Some(capacity) => capacity.get() - inner.buf.len() - inner.reserved,
+                None => this.values.len(),
+            };
+
+            let (head, tail) = this.values.split_at(room.min(this.values.len()));
+
+            if !head.is_empty() {
+                inner.buf.extend(head);
+                this.values = tail;
+
+                if let Some(waker) = &inner.rx {
+                    waker.wake_by_ref();
+                }
+            }
+
+            if this.values.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            if !matches!(&inner.tx, Some(w) if w.will_wake(cx.waker())) {
+                inner.tx = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+/// Future associated with waiting through [Sender::closed].
+struct Closed<'a, T> {
+    sender: &'a mut Sender<T>,
+}
+
+impl<T> Future for Closed<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::into_inner(self).sender.poll_closed(cx)
+    }
+}
+
+/// Future associated with reserving capacity through [Sender::reserve] and
+/// [Sender::reserve_many].
+struct Reserve<'a, T> {
+    inner: &'a BiRc<Shared<T>>,
+    /// The number of slots to reserve.
+    n: usize,
+}
+
+impl<T> Future for Reserve<'_, T> {
+    type Output = Result<(), SendError<()>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+
+            if inner.is_closed(both_present) {
+                inner.tx = None;
+                return Poll::Ready(Err(SendError(())));
+            }
+
+            if !inner.has_capacity(self.n) {
+                if !matches!(&inner.tx, Some(w) if w.will_wake(cx.waker())) {
+                    inner.tx = Some(cx.waker().clone());
+                }
+
+                return Poll::Pending;
+            }
+
+            inner.reserved += self.n;
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+/// A slot reserved through [Sender::reserve] or [Sender::reserve_many].
+///
+/// The slot is released when this is dropped without being used.
+pub struct Permit<'a, T> {
+    inner: &'a BiRc<Shared<T>>,
+}
+
+impl<T> Permit<'_, T> {
+    /// Send a message using the reserved slot.
+    ///
+    /// This never waits, since capacity has already been reserved. If the
+    /// [Receiver] has been dropped the message is dropped as well.
+    pub fn send(self, value: T) {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+
+            if both_present {
+                inner.buf.push_back(value);
+
+                if let Some(waker) = &inner.rx {
+                    waker.wake_by_ref();
+                }
+            }
+        }
+    }
+}
+
+impl<T> Debug for Permit<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Permit").finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Permit<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.inner.get_mut_unchecked().0.release(1);
+        }
+    }
+}
+
+/// An iterator over the slots reserved through [Sender::reserve_many].
+///
+/// Slots which haven't been yielded are released when this is dropped.
+pub struct PermitIterator<'a, T> {
+    inner: &'a BiRc<Shared<T>>,
+    n: usize,
+}
+
+impl<'a, T> Iterator for PermitIterator<'a, T> {
+    type Item = Permit<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n == 0 {
+            return None;
+        }
+
+        self.n -= 1;
+        Some(Permit { inner: self.inner })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.n, Some(self.n))
+    }
+}
+
+impl<T> ExactSizeIterator for PermitIterator<'_, T> {}
+
+impl<T> Debug for PermitIterator<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PermitIterator")
+            .field("n", &self.n)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for PermitIterator<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.inner
+                .get_mut_unchecked()
+                .0
+                .release(mem::take(&mut self.n));
+        }
+    }
+}
+
+/// Future returned when sending a value through [Sender::send].
+///
+/// Resolves to an error with [SendError] if the [Receiver] has been dropped.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SendFut<'a, T> {
+    sender: &'a mut Sender<T>,
+    value: Option<T>,
+}
+
+// The value being sent is never pinned.
+impl<T> Unpin for SendFut<'_, T> {}
+
+impl<'a, T> Future for SendFut<'a, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+
+        let result = match this.sender.poll_ready(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let value = this.value.take().expect("future already completed");
+
+        Poll::Ready(match result {
+            Ok(()) => this
+                .sender
+                .start_send(value)
+                .map_err(|e| SendError(e.into_inner())),
+            Err(SendError(())) => Err(SendError(value)),
+        })
+    }
+}
+
+impl<T> Debug for SendFut<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendFut")
+            .field("completed", &self.value.is_none())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Receiver end of the channel created through [channel].
+pub struct Receiver<T> {
+    inner: BiRc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive a message on this channel.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tokio::task;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(1);
+    ///
+    /// let local = task::LocalSet::new();
+    ///
+    /// let collected = local.run_until(async move {
+    ///     let collect = task::spawn_local(async move {
+    ///         let mut out = Vec::new();
+    ///
+    ///         while let Some(value) = rx.recv().await {
+    ///             out.push(value);
+    ///         }
+    ///
+    ///         out
+    ///     });
+    ///
+    ///     let sender = task::spawn_local(async move {
+    ///         for n in 0..10 {
+    ///             let result = tx.send(n).await;
+    ///         }
+    ///     });
+    ///
+    ///     collect.await
+    /// }).await?;
+    ///
+    /// assert_eq!(collected, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    /// # Ok(()) }
+    /// ```
+    pub fn recv(&mut self) -> RecvFut<'_, T> {
+        RecvFut { receiver: self }
+    }
+
+    /// Close the channel without dropping the receiver.
+    ///
+    /// Any later attempt to send fails with a closed error, and a sender
+    /// waiting for capacity is woken up. Messages which have already been
+    /// sent, or which are sent through outstanding [Permit]s, can still be
+    /// received.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use unsync::spsc::{self, TrySendError};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = spsc::channel(4);
+    /// assert!(tx.try_send(1).is_ok());
+    /// assert!(tx.try_send(2).is_ok());
+    ///
+    /// rx.close();
+    /// assert!(tx.is_closed());
+    /// assert_eq!(tx.try_send(3), Err(TrySendError::Closed(3)));
+    ///
+    /// assert_eq!(rx.recv().await, Some(1));
+    /// assert_eq!(rx.recv().await, Some(2));
+    /// assert_eq!(rx.recv().await, None);
+    /// # }
+    /// ```
+    pub fn close(&mut self) {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.receiver_closed = true;
+
+            if let Some(waker) = inner.tx.take() {
+                waker.wake();
+            }
+
+            if let Some(waker) = inner.closed.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Receive up to `limit` messages on this channel, appending them to
+    /// `buf`.
+    ///
+    /// This waits until at least one message is available, and then receives
+    /// as many buffered messages as it can at once. Resolves to the number of
+    /// messages received, which is `0` once the channel is closed and all
+    /// buffered messages have been received, or if `limit` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(8);
+    ///
+    /// for n in 0..5 {
+    ///     assert!(tx.try_send(n).is_ok());
+    /// }
+    ///
+    /// drop(tx);
+    ///
+    /// let mut buf = Vec::new();
+    /// assert_eq!(rx.recv_many(&mut buf, 3).await, 3);
+    /// assert_eq!(rx.recv_many(&mut buf, 3).await, 2);
+    /// assert_eq!(rx.recv_many(&mut buf, 3).await, 0);
+    /// assert_eq!(buf, vec![0, 1, 2, 3, 4]);
+    /// # }
+    /// ```
+    pub async fn recv_many(&mut self, buf: &mut Vec<T>, limit: usize) -> usize {
+        RecvBatch {
+            inner: &self.inner,
+            limit,
+            read: |queue: &mut VecDeque<T>, n| buf.extend(queue.drain(..n)),
+        }
+        .await
+    }
+
+    /// Receive messages on this channel by copying them into `buf`.
+    ///
+    /// This waits until at least one message is available, and then copies as
+    /// many buffered messages as fit. Resolves to the number of messages
+    /// received, which is `0` once the channel is closed and all buffered
+    /// messages have been received, or if `buf` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(8);
+    /// tx.send_slice(b"hello").await.unwrap();
+    /// drop(tx);
+    ///
+    /// let mut buf = [0; 8];
+    /// assert_eq!(rx.recv_into(&mut buf).await, 5);
+    /// assert_eq!(&buf[..5], b"hello");
+    /// assert_eq!(rx.recv_into(&mut buf).await, 0);
+    /// # }
+    /// ```
+    pub async fn recv_into(&mut self, buf: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        RecvBatch {
+            inner: &self.inner,
+            limit: buf.len(),
+            read: |queue: &mut VecDeque<T>, n| {
+                let (front, back) = queue.as_slices();
+                let split = front.len().min(n);
+                buf[..split].copy_from_slice(&front[..split]);
+                buf[split..n].copy_from_slice(&back[..n - split]);
+                queue.drain(..n);
+            },
+        }
+        .await
+    }
+
+    /// Consume the receiver, returning every message which has been sent but
+    /// not yet received.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(4);
+    ///
+    /// assert!(tx.try_send(1).is_ok());
+    /// assert!(tx.try_send(2).is_ok());
+    /// assert!(tx.try_send(3).is_ok());
+    ///
+    /// assert_eq!(rx.recv().await, Some(1));
+    /// assert_eq!(rx.into_remaining(), vec![2, 3]);
+    /// assert!(tx.is_closed());
+    /// # }
+    /// ```
+    pub fn into_remaining(self) -> Vec<T> {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.buf.drain(..).collect()
+        }
+    }
+
+    /// Try to receive a message on this channel without blocking.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [TryRecvError::Empty] if there are no buffered messages, or
+    /// with [TryRecvError::Closed] if the [Sender] has been dropped or the
+    /// channel has been closed through [Receiver::close], and all buffered
+    /// messages have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use unsync::spsc::{self, TryRecvError};
+    ///
+    /// let (mut tx, mut rx) = spsc::channel(2);
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// assert!(tx.try_send(1).is_ok());
+    /// assert!(tx.try_send(2).is_ok());
+    /// drop(tx);
+    ///
+    /// assert_eq!(rx.try_recv(), Ok(1));
+    /// assert_eq!(rx.try_recv(), Ok(2));
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    /// ```
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+
+            if let Some(value) = inner.buf.pop_front() {
+                // A sender waiting for capacity can now make progress.
+                if let Some(waker) = inner.tx.take() {
+                    waker.wake();
+                }
+
+                return Ok(value);
+            }
+
+            if inner.is_drained(both_present) {
+                return Err(TryRecvError::Closed);
+            }
+
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Poll for the next message on this channel.
+    ///
+    /// If no message is available the current task is registered to be woken
+    /// up once one is sent, or the [Sender] is dropped. Returns
+    /// `Poll::Ready(None)` once the [Sender] has been dropped and all
+    /// buffered messages have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::future::poll_fn;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (mut tx, mut rx) = unsync::spsc::channel(1);
+    /// assert!(tx.try_send(1).is_ok());
+    /// drop(tx);
+    ///
+    /// assert_eq!(poll_fn(|cx| rx.poll_recv(cx)).await, Some(1));
+    /// assert_eq!(poll_fn(|cx| rx.poll_recv(cx)).await, None);
+    /// # }
+    /// ```
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let closed = match self.try_recv() {
+            Ok(value) => return Poll::Ready(Some(value)),
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Closed) => true,
+        };
+
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+
+            if closed {
+                inner.rx = None;
+                return Poll::Ready(None);
+            }
+
+            if !matches!(&inner.rx, Some(w) if w.will_wake(cx.waker())) {
+                inner.rx = Some(cx.waker().clone())
+            }
+
+            if let Some(tx) = &inner.tx {
+                tx.wake_by_ref();
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+/// Future associated with receiving through [Receiver::recv_many] and
+/// [Receiver::recv_into].
+struct RecvBatch<'a, T, F> {
+    inner: &'a BiRc<Shared<T>>,
+    /// The maximum number of messages to receive.
+    limit: usize,
+    /// Reads the given number of messages from the front of the queue.
+    read: F,
+}
+
+// The reader is never pinned.
+impl<T, F> Unpin for RecvBatch<'_, T, F> {}
+
+impl<T, F> Future for RecvBatch<'_, T, F>
+where
+    F: FnMut(&mut VecDeque<T>, usize),
+{
+    type Output = usize;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+
+        unsafe {
+            let (inner, both_present) = this.inner.get_mut_unchecked();
+
+            if this.limit == 0 {
+                return Poll::Ready(0);
+            }
+
+            let n = inner.buf.len().min(this.limit);
+
+            if n > 0 {
+                (this.read)(&mut inner.buf, n);
+
+                // A sender waiting for capacity can now make progress.
+                if let Some(waker) = inner.tx.take() {
+                    waker.wake();
+                }
+
+                return Poll::Ready(n);
+            }
+
+            if inner.is_drained(both_present) {
+                inner.rx = None;
+                return Poll::Ready(0);
+            }
+
+            if !matches!(&inner.rx, Some(w) if w.will_wake(cx.waker())) {
+                inner.rx = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+/// Future associated with receiving through [Receiver::recv].
+///
+/// Resolves to `None` once the [Sender] has been dropped and all buffered
+/// messages have been received.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RecvFut<'a, T> {
+    receiver: &'a mut Receiver<T>,
+}
+
+impl<'a, T> Future for RecvFut<'a, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::into_inner(self).receiver.poll_recv(cx)
+    }
+}
+
+impl<T> Debug for RecvFut<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecvFut").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> futures_core::Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::into_inner(self).poll_recv(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+            let len = inner.buf.len();
+
+            if !inner.is_drained(both_present) {
+                (len, None)
+            } else {
+                (len, Some(len))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> futures_core::FusedStream for Receiver<T> {
+    fn is_terminated(&self) -> bool {
+        unsafe {
+            let (inner, both_present) = self.inner.get_mut_unchecked();
+            inner.is_drained(both_present) && inner.buf.is_empty()
+        }
+    }
+}
+
+/// The sink applies backpressure through [Sink::poll_ready] and flushing
+/// completes immediately. Closing the sink closes the channel, so the
+/// [Receiver] sees the end of the stream once it has received every buffered
+/// message.
+///
+/// # Panics
+///
+/// [Sink::start_send] panics if the channel is at capacity, since that means
+/// it was called without the sink first being ready.
+///
+/// [Sink::poll_ready]: futures_sink::Sink::poll_ready
+/// [Sink::start_send]: futures_sink::Sink::start_send
+#[cfg(feature = "futures-sink")]
+impl<T> futures_sink::Sink<T> for Sender<T> {
+    type Error = SendError<()>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::into_inner(self).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        match Pin::into_inner(self).start_send(item) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(..)) => panic!("start_send called without the sink being ready"),
+            Err(TrySendError::Closed(..)) => Err(SendError(())),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+            inner.sender_closed = true;
+
+            if let Some(waker) = inner.rx.take() {
+                waker.wake();
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(waker) = self.inner.get_mut_unchecked().0.rx.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let (inner, _) = self.inner.get_mut_unchecked();
+
+            if let Some(waker) = inner.tx.take() {
+                waker.wake();
+            }
+
+            if let Some(waker) = inner.closed.take() {
                 waker.wake();
             }
         }
@@ -309,13 +1391,17 @@ impl<T> Drop for Receiver<T> {
 ///
 /// Panics if `capacity` is set to `0`.
 pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
-    assert!(capacity > 0, "capacity cannot be 0");
+    let capacity = NonZeroUsize::new(capacity).expect("capacity cannot be 0");
 
     let (a, b) = BiRc::new(Shared {
         tx: None,
         rx: None,
-        buf: VecDeque::with_capacity(capacity),
-        unbounded: false,
+        closed: None,
+        receiver_closed: false,
+        buf: VecDeque::with_capacity(capacity.get()),
+        capacity: Some(capacity),
+        sender_closed: false,
+        reserved: 0,
     });
 
     let rx = Receiver { inner: a };
@@ -331,8 +1417,12 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
     let (a, b) = BiRc::new(Shared {
         tx: None,
         rx: None,
+        closed: None,
+        receiver_closed: false,
         buf: VecDeque::new(),
-        unbounded: true,
+        capacity: None,
+        sender_closed: false,
+        reserved: 0,
     });
 
     let rx = Receiver { inner: a };
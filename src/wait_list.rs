@@ -0,0 +1,158 @@
+//! A FIFO queue of parked tasks.
+//!
+//! This is used by primitives which can have an arbitrary number of tasks
+//! waiting on them at the same time, and which need to wake those tasks up in
+//! the order in which they started waiting.
+//!
+//! Waiters are stored in a [Slab] and linked together in a doubly-linked list,
+//! so that a waiter can cheaply be removed from anywhere in the queue once the
+//! future it belongs to is dropped.
+
+use core::task::Waker;
+
+use slab::Slab;
+
+struct Node<T> {
+    /// The waker associated with this waiter.
+    waker: Option<Waker>,
+    /// Data associated with the waiter.
+    value: T,
+    /// The previous waiter in the queue.
+    prev: Option<usize>,
+    /// The next waiter in the queue.
+    next: Option<usize>,
+    /// Indicates if the waiter is still linked into the queue.
+    linked: bool,
+}
+
+/// A FIFO queue of wakers with associated data.
+pub(crate) struct WaitList<T> {
+    nodes: Slab<Node<T>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+}
+
+impl<T> WaitList<T> {
+    /// Construct a new empty wait list.
+    pub(crate) fn new() -> Self {
+        Self {
+            nodes: Slab::new(),
+            head: None,
+            tail: None,
+        }
+    }
+
+    /// Test if the wait list is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Test if the waiter with the given key is at the front of the queue.
+    pub(crate) fn is_front(&self, key: usize) -> bool {
+        self.head == Some(key)
+    }
+
+    /// Push a new waiter to the back of the queue and return its key.
+    pub(crate) fn push_back(&mut self, value: T, waker: &Waker) -> usize {
+        let key = self.nodes.insert(Node {
+            waker: Some(waker.clone()),
+            value,
+            prev: self.tail,
+            next: None,
+            linked: true,
+        });
+
+        match self.tail {
+            Some(tail) => self.nodes[tail].next = Some(key),
+            None => self.head = Some(key),
+        }
+
+        self.tail = Some(key);
+        key
+    }
+
+    /// Update the waker associated with the given waiter.
+    ///
+    /// This avoids cloning the waker if the stored one would already wake the
+    /// same task.
+    pub(crate) fn register(&mut self, key: usize, waker: &Waker) {
+        let node = &mut self.nodes[key];
+
+        if !matches!(&node.waker, Some(w) if w.will_wake(waker)) {
+            node.waker = Some(waker.clone());
+        }
+    }
+
+    /// Access the data associated with the waiter at the front of the queue.
+    pub(crate) fn front(&self) -> Option<&T> {
+        Some(&self.nodes[self.head?].value)
+    }
+
+    /// Access the data associated with the given waiter.
+    pub(crate) fn get_mut(&mut self, key: usize) -> &mut T {
+        &mut self.nodes[key].value
+    }
+
+    /// Remove the waiter with the given key, returning its associated data.
+    pub(crate) fn remove(&mut self, key: usize) -> T {
+        let node = self.nodes.remove(key);
+
+        if node.linked {
+            self.unlink(node.prev, node.next);
+        }
+
+        node.value
+    }
+
+    /// Wake the waiter at the front of the queue and unlink it, returning its
+    /// key.
+    ///
+    /// The waiter can still be accessed through its key until it's removed,
+    /// but it's no longer part of the queue.
+    pub(crate) fn pop_front(&mut self) -> Option<usize> {
+        let head = self.head?;
+        let node = &mut self.nodes[head];
+        let next = node.next;
+
+        node.linked = false;
+        node.next = None;
+
+        if let Some(waker) = node.waker.take() {
+            waker.wake();
+        }
+
+        self.unlink(None, next);
+        Some(head)
+    }
+
+    /// Wake the waiter at the front of the queue, if there is one.
+    pub(crate) fn wake_front(&self) {
+        if let Some(head) = self.head {
+            if let Some(waker) = &self.nodes[head].waker {
+                waker.wake_by_ref();
+            }
+        }
+    }
+
+    /// Unlink a node with the given neighbours from the queue.
+    fn unlink(&mut self, prev: Option<usize>, next: Option<usize>) {
+        match prev {
+            Some(prev) => self.nodes[prev].next = next,
+            None => self.head = next,
+        }
+
+        match next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    /// Wake every waiter.
+    pub(crate) fn wake_all(&mut self) {
+        for (_, node) in &mut self.nodes {
+            if let Some(waker) = node.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
@@ -0,0 +1,426 @@
+//! An unsynchronized watch channel, holding a single value which can be
+//! observed by many receivers.
+//!
+//! Unlike [broadcast][crate::broadcast] only the latest value is retained.
+//! Sending never blocks, and receivers which are slow to observe changes
+//! simply see the most recent value once they get around to it. This makes it
+//! useful for sharing state where the latest value wins.
+//!
+//! Each [Receiver] keeps track of the version of the value it has last seen,
+//! so that it can wait for it to change through [Receiver::changed].
+//!
+//! This allocates storage internally to maintain shared state between the
+//! [Sender] and [Receiver]s.
+
+use alloc::rc::Rc;
+use core::cell::{Cell, Ref, RefCell};
+use core::fmt::{self, Debug, Display, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::wait_list::WaitList;
+
+/// Error raised when sending a value over a channel which has no receivers.
+///
+/// # Examples
+///
+/// ```
+/// use unsync::watch;
+///
+/// let (tx, rx) = watch::channel(0);
+/// drop(rx);
+/// assert_eq!(tx.send(1), Err(watch::SendError(1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SendError<T>(pub T);
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "channel disconnected")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SendError<T> where T: fmt::Debug {}
+
+/// Error raised by [Receiver::changed] when the [Sender] has been dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RecvError;
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "channel disconnected")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecvError {}
+
+/// Interior shared state.
+struct Shared<T> {
+    /// The current value.
+    value: RefCell<T>,
+    /// The version of the current value, bumped every time it's modified.
+    version: Cell<u64>,
+    /// The number of live receivers.
+    receivers: Cell<usize>,
+    /// Indicates if the sender has been dropped.
+    closed: Cell<bool>,
+    /// Receivers waiting for the value to change.
+    waiters: RefCell<WaitList<()>>,
+}
+
+impl<T> Shared<T> {
+    /// Mark the current value as modified and notify waiting receivers.
+    fn bump_version(&self) {
+        self.version.set(self.version.get().wrapping_add(1));
+        self.waiters.borrow_mut().wake_all();
+    }
+}
+
+/// Sender end of the channel created through [channel].
+pub struct Sender<T> {
+    inner: Rc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send a new value, notifying all receivers.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [SendError] if there are no receivers, in which case the
+    /// value is handed back and the current value is left as-is. Use
+    /// [Sender::send_modify] to update the value regardless.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed through [Receiver::borrow]
+    /// or [Sender::borrow].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::watch;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, mut rx) = watch::channel(0);
+    ///
+    /// assert!(tx.send(1).is_ok());
+    /// assert!(tx.send(2).is_ok());
+    ///
+    /// // Only the latest value is observed.
+    /// assert!(rx.changed().await.is_ok());
+    /// assert_eq!(*rx.borrow(), 2);
+    /// # }
+    /// ```
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.inner.receivers.get() == 0 {
+            return Err(SendError(value));
+        }
+
+        *self.inner.value.borrow_mut() = value;
+        self.inner.bump_version();
+        Ok(())
+    }
+
+    /// Modify the current value in place, notifying all receivers.
+    ///
+    /// Unlike [Sender::send] this updates the value even if there are no
+    /// receivers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed through [Receiver::borrow]
+    /// or [Sender::borrow].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::watch;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, mut rx) = watch::channel(vec![1]);
+    ///
+    /// tx.send_modify(|v| v.push(2));
+    ///
+    /// assert!(rx.changed().await.is_ok());
+    /// assert_eq!(*rx.borrow(), vec![1, 2]);
+    /// # }
+    /// ```
+    pub fn send_modify<F>(&self, modify: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        modify(&mut self.inner.value.borrow_mut());
+        self.inner.bump_version();
+    }
+
+    /// Borrow the current value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently being modified.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.value.borrow()
+    }
+
+    /// Subscribe to the watch channel.
+    ///
+    /// The current value is considered seen by the returned [Receiver], so
+    /// [Receiver::changed] will only complete once a new value is sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::watch;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, rx) = watch::channel(1);
+    /// drop(rx);
+    ///
+    /// tx.send_modify(|v| *v = 2);
+    ///
+    /// let mut rx = tx.subscribe();
+    /// assert_eq!(*rx.borrow(), 2);
+    ///
+    /// assert!(tx.send(3).is_ok());
+    /// assert!(rx.changed().await.is_ok());
+    /// assert_eq!(*rx.borrow(), 3);
+    /// # }
+    /// ```
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.inner.receivers.set(self.inner.receivers.get() + 1);
+
+        Receiver {
+            inner: self.inner.clone(),
+            version: self.inner.version.get(),
+        }
+    }
+
+    /// Get a count on the number of subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.inner.receivers.get()
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.inner.closed.set(true);
+        self.inner.waiters.borrow_mut().wake_all();
+    }
+}
+
+/// Receiver end of the channel created through [channel] or
+/// [Sender::subscribe].
+///
+/// Receivers can be cloned, the clone starts out having seen the same version
+/// of the value as the receiver it was cloned from.
+pub struct Receiver<T> {
+    inner: Rc<Shared<T>>,
+    /// The version of the value last seen by this receiver.
+    version: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Borrow the current value without marking it as seen.
+    ///
+    /// Note that holding on to the returned reference prevents the [Sender]
+    /// from updating the value, so it should be dropped as soon as possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently being modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::watch;
+    ///
+    /// let (tx, rx) = watch::channel(String::from("hello"));
+    /// assert_eq!(*rx.borrow(), "hello");
+    /// ```
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.value.borrow()
+    }
+
+    /// Borrow the current value and mark it as seen.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently being modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unsync::watch;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() {
+    /// let (tx, mut rx) = watch::channel(1);
+    /// assert!(tx.send(2).is_ok());
+    ///
+    /// assert_eq!(*rx.borrow_and_update(), 2);
+    ///
+    /// drop(tx);
+    /// // Nothing new to see, and the sender is gone.
+    /// assert!(rx.changed().await.is_err());
+    /// # }
+    /// ```
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.version = self.inner.version.get();
+        self.inner.value.borrow()
+    }
+
+    /// Wait for the value to change from the version last seen by this
+    /// receiver, and mark the new version as seen.
+    ///
+    /// This completes immediately if the value has changed since it was last
+    /// seen. Use [Receiver::borrow] to access the new value.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [RecvError] if the [Sender] has been dropped and there is
+    /// no unseen value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::task;
+    /// use unsync::watch;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")] async fn main() -> Result<(), task::JoinError> {
+    /// let (tx, mut rx) = watch::channel(0);
+    ///
+    /// let local = task::LocalSet::new();
+    ///
+    /// let seen = local.run_until(async move {
+    ///     let watcher = task::spawn_local(async move {
+    ///         let mut seen = Vec::new();
+    ///
+    ///         while rx.changed().await.is_ok() {
+    ///             seen.push(*rx.borrow());
+    ///         }
+    ///
+    ///         seen
+    ///     });
+    ///
+    ///     for n in 1..=3 {
+    ///         task::yield_now().await;
+    ///         let _ = tx.send(n);
+    ///     }
+    ///
+    ///     drop(tx);
+    ///     watcher.await
+    /// }).await?;
+    ///
+    /// assert_eq!(seen.last(), Some(&3));
+    /// # Ok(()) }
+    /// ```
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        Changed {
+            receiver: self,
+            key: None,
+        }
+        .await
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.inner.receivers.set(self.inner.receivers.get() + 1);
+
+        Self {
+            inner: self.inner.clone(),
+            version: self.version,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.receivers.set(self.inner.receivers.get() - 1);
+    }
+}
+
+/// Future associated with waiting through [Receiver::changed].
+struct Changed<'a, T> {
+    receiver: &'a mut Receiver<T>,
+    /// Our key in the wait list, if we are waiting for a change.
+    key: Option<usize>,
+}
+
+impl<'a, T> Future for Changed<'a, T> {
+    type Output = Result<(), RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        let inner = &this.receiver.inner;
+        let mut waiters = inner.waiters.borrow_mut();
+
+        let version = inner.version.get();
+
+        if this.receiver.version != version || inner.closed.get() {
+            if let Some(key) = this.key.take() {
+                waiters.remove(key);
+            }
+
+            if this.receiver.version == version {
+                return Poll::Ready(Err(RecvError));
+            }
+
+            this.receiver.version = version;
+            return Poll::Ready(Ok(()));
+        }
+
+        match this.key {
+            Some(key) => waiters.register(key, cx.waker()),
+            None => this.key = Some(waiters.push_back((), cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Changed<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.receiver.inner.waiters.borrow_mut().remove(key);
+        }
+    }
+}
+
+/// Setup a watch channel holding the given initial value.
+///
+/// The initial value is considered seen by the returned [Receiver].
+///
+/// # Examples
+///
+/// ```
+/// use unsync::watch;
+///
+/// # #[tokio::main(flavor = "current_thread")] async fn main() {
+/// let (tx, mut rx) = watch::channel("hello");
+/// assert_eq!(*rx.borrow(), "hello");
+///
+/// assert!(tx.send("world").is_ok());
+/// assert!(rx.changed().await.is_ok());
+/// assert_eq!(*rx.borrow(), "world");
+/// # }
+/// ```
+pub fn channel<T>(value: T) -> (Sender<T>, Receiver<T>) {
+    let inner = Rc::new(Shared {
+        value: RefCell::new(value),
+        version: Cell::new(0),
+        receivers: Cell::new(1),
+        closed: Cell::new(false),
+        waiters: RefCell::new(WaitList::new()),
+    });
+
+    let rx = Receiver {
+        inner: inner.clone(),
+        version: 0,
+    };
+
+    let tx = Sender { inner };
+    (tx, rx)
+}
@@ -0,0 +1,131 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use tokio::task;
+use unsync::barrier::{Barrier, WaitGroup};
+
+#[cfg(not(miri))]
+const SIZE: u32 = 1_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+#[tokio::test]
+async fn test_barrier_phases() -> Result<(), Box<dyn std::error::Error>> {
+    const TASKS: usize = 8;
+
+    let local = task::LocalSet::new();
+
+    let barrier = Rc::new(Barrier::new(TASKS));
+    let phase = Rc::new(Cell::new(0u32));
+    let leaders = Rc::new(Cell::new(0u32));
+
+    local
+        .run_until(async {
+            let mut tasks = Vec::new();
+
+            for _ in 0..TASKS {
+                let barrier = barrier.clone();
+                let phase = phase.clone();
+                let leaders = leaders.clone();
+
+                tasks.push(task::spawn_local(async move {
+                    for n in 0..SIZE {
+                        // Nobody can get ahead of the current phase.
+                        assert_eq!(phase.get(), n);
+
+                        if barrier.wait().await.is_leader() {
+                            leaders.set(leaders.get() + 1);
+                            phase.set(n + 1);
+                        }
+
+                        // Make sure the leader has advanced the phase before
+                        // anyone moves on.
+                        barrier.wait().await;
+                    }
+                }));
+            }
+
+            for t in tasks {
+                t.await?;
+            }
+
+            Ok::<_, task::JoinError>(())
+        })
+        .await?;
+
+    assert_eq!(leaders.get(), SIZE);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_barrier_cancelled_wait() {
+    let local = task::LocalSet::new();
+
+    let barrier = Rc::new(Barrier::new(2));
+
+    local
+        .run_until(async {
+            let cancelled = task::spawn_local({
+                let barrier = barrier.clone();
+                async move { barrier.wait().await }
+            });
+
+            task::yield_now().await;
+            cancelled.abort();
+            assert!(cancelled.await.unwrap_err().is_cancelled());
+
+            // The aborted task no longer counts, so two more are needed.
+            let first = task::spawn_local({
+                let barrier = barrier.clone();
+                async move { barrier.wait().await.is_leader() }
+            });
+
+            task::yield_now().await;
+
+            let second = barrier.wait().await;
+            assert!(second.is_leader());
+            assert!(!first.await.unwrap());
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_wait_group() {
+    let local = task::LocalSet::new();
+
+    let done = Rc::new(Cell::new(0));
+    let wg = WaitGroup::new();
+
+    local
+        .run_until(async {
+            for n in 0..16 {
+                let wg = wg.clone();
+                let done = done.clone();
+
+                task::spawn_local(async move {
+                    for _ in 0..n {
+                        task::yield_now().await;
+                    }
+
+                    done.set(done.get() + 1);
+                    drop(wg);
+                });
+            }
+
+            let observer = task::spawn_local({
+                let wg = wg.clone();
+                let done = done.clone();
+
+                async move {
+                    wg.wait().await;
+                    done.get()
+                }
+            });
+
+            wg.wait().await;
+            assert_eq!(done.get(), 16);
+            assert_eq!(observer.await.unwrap(), 16);
+        })
+        .await;
+}
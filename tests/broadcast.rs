@@ -1,3 +1,6 @@
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::Poll;
 use tokio::task;
 use unsync::broadcast;
 
@@ -88,3 +91,473 @@ async fn test_broadcast_receiver_drop() {
     let (send,) = tokio::join!(tx.send(2));
     assert_eq!(send, 0);
 }
+
+#[tokio::test]
+async fn test_lossy_lagging_receiver() {
+    let mut tx = broadcast::lossy::<u32>(4);
+
+    let mut fast = tx.subscribe();
+    let mut slow = tx.subscribe();
+
+    for n in 0..10 {
+        assert_eq!(tx.send(n), 2);
+        assert_eq!(fast.recv().await, Ok(n));
+    }
+
+    // The slow receiver skips ahead to the oldest retained message.
+    assert_eq!(slow.recv().await, Err(broadcast::RecvError::Lagged(6)));
+
+    for n in 6..10 {
+        assert_eq!(slow.recv().await, Ok(n));
+    }
+
+    drop(tx);
+    assert_eq!(fast.recv().await, Err(broadcast::RecvError::Closed));
+    assert_eq!(slow.recv().await, Err(broadcast::RecvError::Closed));
+}
+
+#[tokio::test]
+async fn test_lossy_sender_never_waits() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let mut tx = broadcast::lossy::<u32>(8);
+    let mut stalled = tx.subscribe();
+
+    let (received, lagged) = local
+        .run_until(async move {
+            let mut rx = tx.subscribe();
+
+            let receiver = task::spawn_local(async move {
+                let mut received = Vec::new();
+
+                loop {
+                    match rx.recv().await {
+                        Ok(value) => received.push(value),
+                        Err(broadcast::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::RecvError::Closed) => break,
+                    }
+                }
+
+                received
+            });
+
+            for n in 0..SIZE {
+                tx.send(n);
+
+                if n % 4 == 0 {
+                    task::yield_now().await;
+                }
+            }
+
+            drop(tx);
+
+            let mut lagged = 0;
+
+            while let Err(broadcast::RecvError::Lagged(n)) = stalled.recv().await {
+                lagged += n;
+            }
+
+            Ok::<_, task::JoinError>((receiver.await?, lagged))
+        })
+        .await?;
+
+    assert_eq!(received, (0..SIZE).collect::<Vec<_>>());
+    assert_eq!(lagged, u64::from(SIZE) - 8);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_try_send_all_or_nothing() {
+    let mut tx = broadcast::channel::<u32>(2);
+    let mut sub1 = tx.subscribe();
+    let mut sub2 = tx.subscribe();
+
+    assert_eq!(tx.try_send(1), Ok(2));
+    assert_eq!(tx.try_send(2), Ok(2));
+    assert_eq!(sub2.recv().await, Some(1));
+    assert_eq!(sub2.recv().await, Some(2));
+
+    // The first subscriber is at capacity, so nobody receives these.
+    assert_eq!(tx.try_send(3), Err(broadcast::UnderCapacity));
+    assert_eq!(tx.try_send_all(4), Err(broadcast::TrySendError::Full(4)));
+
+    assert_eq!(sub1.recv().await, Some(1));
+    assert_eq!(tx.try_send(5), Ok(2));
+
+    drop(tx);
+
+    assert_eq!(sub1.recv().await, Some(2));
+    assert_eq!(sub1.recv().await, Some(5));
+    assert_eq!(sub1.recv().await, None);
+
+    assert_eq!(sub2.recv().await, Some(5));
+    assert_eq!(sub2.recv().await, None);
+}
+
+#[tokio::test]
+async fn test_shared_drops_after_last_subscriber() {
+    use std::rc::Rc;
+
+    struct Payload {
+        _alive: Rc<()>,
+    }
+
+    let alive = Rc::new(());
+
+    let mut tx = broadcast::shared::<Payload>(2);
+    let mut sub1 = tx.subscribe();
+    let mut sub2 = tx.subscribe();
+
+    assert_eq!(
+        tx.try_send(Payload {
+            _alive: alive.clone()
+        }),
+        Ok(2)
+    );
+    assert_eq!(Rc::strong_count(&alive), 2);
+
+    let first = sub1.recv().await.unwrap();
+    drop(first);
+    assert_eq!(Rc::strong_count(&alive), 2);
+
+    let second = sub2.recv().await.unwrap();
+    assert_eq!(Rc::strong_count(&alive), 2);
+    drop(second);
+    assert_eq!(Rc::strong_count(&alive), 1);
+
+    assert_eq!(
+        tx.try_send(Payload {
+            _alive: alive.clone()
+        }),
+        Ok(2)
+    );
+    drop(sub1);
+    drop(sub2);
+    assert_eq!(Rc::strong_count(&alive), 1);
+}
+
+#[tokio::test]
+async fn test_recv_cancel_safe() {
+    let mut tx = broadcast::channel::<u32>(4);
+    let mut sub = tx.subscribe();
+
+    // Cancel a receive which is waiting for a message.
+    let mut recv = sub.recv();
+    poll_fn(|cx| {
+        assert!(Pin::new(&mut recv).poll(cx).is_pending());
+        Poll::Ready(())
+    })
+    .await;
+    drop(recv);
+
+    assert_eq!(tx.try_send(1), Ok(1));
+    assert_eq!(tx.try_send(2), Ok(1));
+
+    // Cancel a receive before it had the chance to complete.
+    drop(sub.recv());
+
+    assert_eq!(sub.recv().await, Some(1));
+    assert_eq!(sub.recv().await, Some(2));
+}
+
+#[tokio::test]
+async fn test_recv_select_loop() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let mut tx = broadcast::channel::<u32>(2);
+
+    let (received, cancelled) = local
+        .run_until(async move {
+            let mut receivers = Vec::new();
+
+            for _ in 0..4 {
+                let mut rx = tx.subscribe();
+
+                receivers.push(task::spawn_local(async move {
+                    let mut received = Vec::new();
+                    let mut cancelled = 0;
+
+                    loop {
+                        tokio::select! {
+                            value = rx.recv() => match value {
+                                Some(value) => received.push(value),
+                                None => break,
+                            },
+                            _ = task::yield_now() => cancelled += 1,
+                        }
+                    }
+
+                    (received, cancelled)
+                }));
+            }
+
+            task::spawn_local(async move {
+                for n in 0..SIZE {
+                    tx.send(n).await;
+
+                    if n % 3 == 0 {
+                        task::yield_now().await;
+                    }
+                }
+            });
+
+            let mut received = Vec::new();
+            let mut cancelled = 0;
+
+            for receiver in receivers {
+                let (r, c) = receiver.await?;
+                received.push(r);
+                cancelled += c;
+            }
+
+            Ok::<_, task::JoinError>((received, cancelled))
+        })
+        .await?;
+
+    assert!(cancelled > 0);
+
+    let expected = (0..SIZE).collect::<Vec<_>>();
+
+    for actual in received {
+        assert_eq!(actual, expected);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_cancelled_delivers_to_nobody() {
+    let mut tx = broadcast::channel::<u32>(1);
+    let mut sub1 = tx.subscribe();
+    let mut sub2 = tx.subscribe();
+
+    assert_eq!(tx.try_send_all(1), Ok(2));
+    assert_eq!(sub1.recv().await, Some(1));
+
+    // The channel is full until the second subscriber catches up, so this
+    // send is pending when it's cancelled.
+    let mut send = tx.send(2);
+    poll_fn(|cx| {
+        assert!(Pin::new(&mut send).poll(cx).is_pending());
+        Poll::Ready(())
+    })
+    .await;
+    drop(send);
+
+    assert_eq!(tx.try_send_all(3), Err(broadcast::TrySendError::Full(3)));
+    assert_eq!(sub2.recv().await, Some(1));
+    assert_eq!(tx.try_send_all(3), Ok(2));
+
+    drop(tx);
+    assert_eq!(sub1.recv().await, Some(3));
+    assert_eq!(sub1.recv().await, None);
+    assert_eq!(sub2.recv().await, Some(3));
+    assert_eq!(sub2.recv().await, None);
+}
+
+#[tokio::test]
+async fn test_reserve() {
+    let mut tx = broadcast::channel::<u32>(2);
+    let mut sub1 = tx.subscribe();
+    let mut sub2 = tx.subscribe();
+
+    let mut permits = tx.reserve_many(2).await;
+    assert_eq!(permits.next().map(|permit| permit.send(1)), Some(2));
+    drop(permits);
+
+    // The unused slot was released.
+    assert_eq!(tx.try_send(2), Ok(2));
+    assert_eq!(sub1.recv().await, Some(1));
+
+    // The second subscriber holds on to both slots, so reserving waits for it
+    // to catch up.
+    let mut reserve = Box::pin(tx.reserve());
+    poll_fn(|cx| {
+        assert!(reserve.as_mut().poll(cx).is_pending());
+        Poll::Ready(())
+    })
+    .await;
+
+    let (permit, s2) = tokio::join!(reserve, sub2.recv());
+    assert_eq!(s2, Some(1));
+    assert_eq!(permit.send(3), 2);
+
+    assert_eq!(sub1.recv().await, Some(2));
+    assert_eq!(sub1.recv().await, Some(3));
+    assert_eq!(sub2.recv().await, Some(2));
+    assert_eq!(sub2.recv().await, Some(3));
+}
+
+#[tokio::test]
+async fn test_try_recv() {
+    let mut tx = broadcast::channel::<u32>(2);
+    let mut sub1 = tx.subscribe();
+    let mut sub2 = tx.subscribe();
+
+    assert_eq!(tx.try_send(1), Ok(2));
+    assert_eq!(tx.try_send(2), Ok(2));
+
+    assert_eq!(sub1.try_recv(), Ok(1));
+    assert_eq!(sub1.try_recv(), Ok(2));
+    assert_eq!(sub1.try_recv(), Err(broadcast::TryRecvError::Empty));
+
+    // The slowest subscriber still holds on to both messages.
+    assert_eq!(tx.try_send(3), Err(broadcast::UnderCapacity));
+    assert_eq!(sub2.try_recv(), Ok(1));
+    assert_eq!(tx.try_send(3), Ok(2));
+
+    drop(tx);
+    assert_eq!(sub1.try_recv(), Ok(3));
+    assert_eq!(sub1.try_recv(), Err(broadcast::TryRecvError::Closed));
+    assert_eq!(sub2.recv().await, Some(2));
+    assert_eq!(sub2.recv().await, Some(3));
+    assert_eq!(sub2.try_recv(), Err(broadcast::TryRecvError::Closed));
+}
+
+#[tokio::test]
+async fn test_closed() -> Result<(), task::JoinError> {
+    let local = task::LocalSet::new();
+
+    let mut tx = broadcast::channel::<u32>(1);
+    let subs = (0..4).map(|_| tx.subscribe()).collect::<Vec<_>>();
+
+    local
+        .run_until(async move {
+            let sender = task::spawn_local(async move {
+                tx.closed().await;
+                assert!(tx.is_closed());
+                assert_eq!(tx.subscribers(), 0);
+            });
+
+            for sub in subs {
+                task::yield_now().await;
+                drop(sub);
+            }
+
+            sender.await
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_close_drains_buffer() -> Result<(), task::JoinError> {
+    let local = task::LocalSet::new();
+
+    let mut tx = broadcast::channel::<u32>(2);
+    let mut sub = tx.subscribe();
+
+    local
+        .run_until(async move {
+            let sender = task::spawn_local(async move {
+                let mut sent = 0;
+
+                while tx.send(sent).await > 0 {
+                    sent += 1;
+                }
+
+                sent
+            });
+
+            assert_eq!(sub.recv().await, Some(0));
+            sub.close();
+
+            let sent = sender.await?;
+            let mut received = vec![0];
+
+            while let Some(value) = sub.recv().await {
+                received.push(value);
+            }
+
+            assert_eq!(received, (0..sent).collect::<Vec<_>>());
+            Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_batched() -> Result<(), task::JoinError> {
+    let local = task::LocalSet::new();
+
+    let mut tx = broadcast::channel::<u32>(16);
+    let mut receivers = (0..4).map(|_| tx.subscribe()).collect::<Vec<_>>();
+
+    local
+        .run_until(async move {
+            let handles = receivers
+                .drain(..)
+                .enumerate()
+                .map(|(limit, mut sub)| {
+                    task::spawn_local(async move {
+                        let mut received = Vec::new();
+
+                        while sub.recv_many(&mut received, limit + 1).await > 0 {}
+
+                        received
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(tx.send_all(0..SIZE).await, SIZE as usize);
+            drop(tx);
+
+            for handle in handles {
+                assert_eq!(handle.await?, (0..SIZE).collect::<Vec<_>>());
+            }
+
+            Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_send_all_stops_without_subscribers() {
+    let mut tx = broadcast::channel::<u32>(2);
+    let mut sub = tx.subscribe();
+
+    let (sent, received) = tokio::join!(tx.send_all(0..), async move {
+        let mut received = Vec::new();
+        sub.recv_many(&mut received, 3).await;
+        received
+    });
+
+    assert_eq!(sent, 2);
+    assert_eq!(received, [0, 1]);
+}
+
+#[tokio::test]
+async fn test_recv_many_wakes_sender_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Wake, Waker};
+
+    struct CountWakes(AtomicUsize);
+
+    impl Wake for CountWakes {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut tx = broadcast::channel::<u32>(4);
+    let mut sub = tx.subscribe();
+
+    for n in 0..4 {
+        assert_eq!(tx.try_send(n), Ok(1));
+    }
+
+    let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+    let waker = Waker::from(wakes.clone());
+
+    let mut send = tx.send(4);
+    assert!(Pin::new(&mut send)
+        .poll(&mut Context::from_waker(&waker))
+        .is_pending());
+
+    let mut buf = Vec::new();
+    assert_eq!(sub.recv_many(&mut buf, 8).await, 4);
+    assert_eq!(buf, [0, 1, 2, 3]);
+    assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+
+    assert_eq!(send.await, 1);
+    assert_eq!(sub.recv().await, Some(4));
+}
@@ -0,0 +1,139 @@
+#![cfg(all(feature = "futures-core", feature = "futures-sink"))]
+
+use futures::future::FusedFuture;
+use futures::stream::FusedStream;
+use futures::{SinkExt, Stream, StreamExt};
+use tokio::task;
+use unsync::{broadcast, oneshot, spsc};
+
+#[tokio::test]
+async fn test_spsc_stream_and_sink() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, rx) = spsc::channel(4);
+
+    let collected = local
+        .run_until(async move {
+            let collect = task::spawn_local(rx.collect::<Vec<u32>>());
+
+            SinkExt::send_all(&mut tx, &mut futures::stream::iter(0..100).map(Ok))
+                .await
+                .unwrap();
+            drop(tx);
+
+            collect.await
+        })
+        .await?;
+
+    assert_eq!(collected, (0..100).collect::<Vec<u32>>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_size_hint_and_fused() {
+    let (mut tx, mut rx) = spsc::channel(4);
+    assert!(tx.try_send(1).is_ok());
+    assert!(tx.try_send(2).is_ok());
+    assert_eq!(rx.size_hint(), (2, None));
+
+    drop(tx);
+    assert_eq!(rx.size_hint(), (2, Some(2)));
+    assert!(!rx.is_terminated());
+
+    assert_eq!(rx.next().await, Some(1));
+    assert_eq!(rx.next().await, Some(2));
+    assert_eq!(rx.next().await, None);
+    assert!(rx.is_terminated());
+
+    let mut sender = broadcast::channel::<u32>(2);
+    let mut sub = sender.subscribe();
+    sender.feed(1).await.unwrap();
+    assert_eq!(sub.size_hint(), (1, None));
+    drop(sender);
+    assert_eq!(sub.size_hint(), (1, Some(1)));
+    assert_eq!(sub.next().await, Some(1));
+    assert!(sub.is_terminated());
+
+    let (tx, mut rx) = oneshot::channel();
+    assert!(!rx.is_terminated());
+    assert!(tx.send(1).is_ok());
+    assert_eq!((&mut rx).await, Ok(1));
+    assert!(rx.is_terminated());
+}
+
+#[tokio::test]
+async fn test_broadcast_sink_backpressure() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let mut sender = broadcast::channel::<u32>(1);
+    let sub1 = sender.subscribe();
+    let sub2 = sender.subscribe();
+
+    let (a, b) = local
+        .run_until(async move {
+            let a = task::spawn_local(sub1.collect::<Vec<_>>());
+            let b = task::spawn_local(sub2.collect::<Vec<_>>());
+
+            for n in 0..10 {
+                SinkExt::send(&mut sender, n).await.unwrap();
+            }
+
+            drop(sender);
+            Ok::<_, task::JoinError>((a.await?, b.await?))
+        })
+        .await?;
+
+    assert_eq!(a, (0..10).collect::<Vec<u32>>());
+    assert_eq!(b, (0..10).collect::<Vec<u32>>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sink_close_ends_stream() {
+    let (mut tx, rx) = spsc::channel(4);
+    tx.feed(1).await.unwrap();
+    tx.feed(2).await.unwrap();
+    tx.close().await.unwrap();
+
+    assert!(tx.is_closed());
+    assert!(matches!(tx.try_send(3), Err(spsc::TrySendError::Closed(3))));
+    assert_eq!(rx.collect::<Vec<u32>>().await, vec![1, 2]);
+
+    let mut sender = broadcast::channel::<u32>(4);
+    let sub = sender.subscribe();
+    sender.feed(1).await.unwrap();
+    sender.close().await.unwrap();
+
+    assert_eq!(sender.try_send(2), Ok(0));
+    assert_eq!(
+        sender.try_send_all(2),
+        Err(broadcast::TrySendError::Closed(2))
+    );
+    assert_eq!(sub.collect::<Vec<u32>>().await, vec![1]);
+}
+
+#[test]
+fn test_broadcast_start_send_without_capacity() {
+    use futures::Sink;
+    use std::pin::Pin;
+
+    let mut sender = broadcast::channel::<u32>(1);
+    let _sub = sender.subscribe();
+
+    assert_eq!(Pin::new(&mut sender).start_send(1), Ok(()));
+    assert_eq!(
+        Pin::new(&mut sender).start_send(2),
+        Err(broadcast::UnderCapacity)
+    );
+}
+
+#[test]
+#[should_panic = "start_send called without the sink being ready"]
+fn test_spsc_start_send_without_capacity() {
+    use futures::Sink;
+    use std::pin::Pin;
+
+    let (mut tx, _rx) = spsc::channel::<u32>(1);
+    let _ = Pin::new(&mut tx).start_send(1);
+    let _ = Pin::new(&mut tx).start_send(2);
+}
@@ -0,0 +1,123 @@
+use tokio::task;
+use unsync::mpmc;
+
+#[cfg(not(miri))]
+const SIZE: u32 = 100_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+#[tokio::test]
+async fn test_mpmc() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let (tx, rx) = mpmc::channel(2);
+
+    let (workers, senders) = local
+        .run_until(async move {
+            let mut workers = Vec::new();
+
+            for _ in 0..4 {
+                let rx = rx.clone();
+
+                workers.push(task::spawn_local(async move {
+                    let mut out = Vec::new();
+
+                    while let Some(value) = rx.recv().await {
+                        out.push(value);
+
+                        if value % 3 == 0 {
+                            task::yield_now().await;
+                        }
+                    }
+
+                    out
+                }));
+            }
+
+            drop(rx);
+
+            let mut senders = Vec::new();
+
+            for s in 0..2 {
+                let tx = tx.clone();
+
+                senders.push(task::spawn_local(async move {
+                    for n in (s..SIZE).step_by(2) {
+                        let _ = tx.send(n).await;
+
+                        if n % 5 == 0 {
+                            task::yield_now().await;
+                        }
+                    }
+                }));
+            }
+
+            drop(tx);
+
+            let mut received = Vec::new();
+
+            for worker in workers {
+                received.push(worker.await);
+            }
+
+            let mut results = Vec::new();
+
+            for sender in senders {
+                results.push(sender.await);
+            }
+
+            (received, results)
+        })
+        .await;
+
+    for sender in senders {
+        let () = sender?;
+    }
+
+    let mut actual = Vec::new();
+
+    for worker in workers {
+        actual.extend(worker?);
+    }
+
+    actual.sort();
+
+    let expected = (0..SIZE).collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_drain_after_close() {
+    let (tx, rx) = mpmc::unbounded();
+    let rx2 = rx.clone();
+
+    for n in 0..4 {
+        assert!(tx.try_send(n).is_ok());
+    }
+
+    drop(tx);
+
+    assert_eq!(rx.recv().await, Some(0));
+    assert_eq!(rx2.recv().await, Some(1));
+    assert_eq!(rx2.recv().await, Some(2));
+    assert_eq!(rx.recv().await, Some(3));
+    assert_eq!(rx.recv().await, None);
+    assert_eq!(rx2.recv().await, None);
+}
+
+#[tokio::test]
+async fn test_receivers_drop() {
+    let (tx, rx) = mpmc::channel(1);
+    let rx2 = rx.clone();
+
+    assert!(tx.send(1).await.is_ok());
+    drop(rx);
+    assert!(tx.try_send(2).is_err());
+
+    assert_eq!(rx2.recv().await, Some(1));
+    drop(rx2);
+
+    assert_eq!(tx.send(3).await, Err(mpmc::SendError(3)));
+}
@@ -0,0 +1,132 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tokio::task;
+use unsync::mpsc;
+
+#[cfg(not(miri))]
+const SIZE: u32 = 100_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+const SENDERS: u32 = 4;
+
+#[tokio::test]
+async fn test_mpsc() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let (tx, mut rx) = mpsc::channel(2);
+
+    let (a, senders) = local
+        .run_until(async move {
+            let a = task::spawn_local(async move {
+                let mut out = Vec::new();
+
+                while let Some(value) = rx.recv().await {
+                    out.push(value);
+
+                    if value % 3 == 0 {
+                        task::yield_now().await;
+                    }
+                }
+
+                out
+            });
+
+            let mut senders = Vec::new();
+
+            for s in 0..SENDERS {
+                let tx = tx.clone();
+
+                senders.push(task::spawn_local(async move {
+                    for n in (s..SIZE).step_by(SENDERS as usize) {
+                        let _ = tx.send(n).await;
+
+                        if n % 5 == 0 {
+                            task::yield_now().await;
+                        }
+                    }
+                }));
+            }
+
+            drop(tx);
+
+            let a = a.await;
+            let mut results = Vec::new();
+
+            for sender in senders {
+                results.push(sender.await);
+            }
+
+            (a, results)
+        })
+        .await;
+
+    for sender in senders {
+        let () = sender?;
+    }
+
+    let mut actual = a?;
+    actual.sort();
+
+    let expected = (0..SIZE).collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fair_senders() {
+    let local = task::LocalSet::new();
+
+    let (tx, mut rx) = mpsc::channel(1);
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    local
+        .run_until(async move {
+            tx.send(0).await.unwrap();
+
+            let mut senders = Vec::new();
+
+            for n in 1..=4 {
+                let tx = tx.clone();
+                let order = order.clone();
+
+                senders.push(task::spawn_local(async move {
+                    tx.send(n).await.unwrap();
+                    order.borrow_mut().push(n);
+                }));
+
+                // Make sure each sender is blocked before spawning the next.
+                task::yield_now().await;
+            }
+
+            drop(tx);
+
+            let mut received = Vec::new();
+
+            while let Some(value) = rx.recv().await {
+                received.push(value);
+            }
+
+            for sender in senders {
+                sender.await.unwrap();
+            }
+
+            assert_eq!(received, vec![0, 1, 2, 3, 4]);
+            assert_eq!(*order.borrow(), vec![1, 2, 3, 4]);
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_receiver_drop() {
+    let (tx, rx) = mpsc::channel(1);
+    let tx2 = tx.clone();
+
+    assert!(tx.send(1).await.is_ok());
+    drop(rx);
+
+    assert_eq!(tx2.send(2).await, Err(mpsc::SendError(2)));
+    assert_eq!(tx.try_send(3), Err(mpsc::SendError(3)));
+}
@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use tokio::task;
+use unsync::mutex::Mutex;
+
+#[cfg(not(miri))]
+const SIZE: u32 = 10_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+#[tokio::test]
+async fn test_mutex() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let mutex = Rc::new(Mutex::new(0u32));
+
+    local
+        .run_until(async {
+            let mut tasks = Vec::new();
+
+            for _ in 0..8 {
+                let mutex = mutex.clone();
+
+                tasks.push(task::spawn_local(async move {
+                    for n in 0..SIZE {
+                        let mut guard = mutex.lock().await;
+                        let value = *guard;
+
+                        if n % 3 == 0 {
+                            task::yield_now().await;
+                        }
+
+                        *guard = value + 1;
+                    }
+                }));
+            }
+
+            for t in tasks {
+                t.await?;
+            }
+
+            Ok::<_, task::JoinError>(())
+        })
+        .await?;
+
+    assert_eq!(*mutex.lock().await, SIZE * 8);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fairness_and_cancellation() {
+    let local = task::LocalSet::new();
+
+    let mutex = Rc::new(Mutex::new(Vec::new()));
+
+    local
+        .run_until(async {
+            let guard = mutex.clone().lock_owned().await;
+
+            let mut tasks = Vec::new();
+
+            for n in 0..4 {
+                let mutex = mutex.clone();
+
+                tasks.push(task::spawn_local(async move {
+                    mutex.lock().await.push(n);
+                }));
+
+                task::yield_now().await;
+            }
+
+            // Waiters are queued, so the lock can't be stolen even once it's
+            // released.
+            tasks.remove(1).abort();
+            drop(guard);
+            assert!(mutex.try_lock().is_err());
+
+            for t in tasks {
+                t.await.unwrap();
+            }
+        })
+        .await;
+
+    assert_eq!(*mutex.lock().await, vec![0, 2, 3]);
+}
+
+#[test]
+fn test_map_panic_releases_lock() {
+    use std::panic::{self, AssertUnwindSafe};
+    use unsync::mutex::{MappedMutexGuard, MutexGuard};
+
+    let mutex = Mutex::new((1u32, 2u32));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let guard = mutex.try_lock().unwrap();
+        MutexGuard::map(guard, |_| -> &mut u32 { panic!("map failed") })
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(*mutex.try_lock().unwrap(), (1, 2));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let guard = MutexGuard::map(mutex.try_lock().unwrap(), |(a, _)| a);
+        MappedMutexGuard::map(guard, |_| -> &mut u32 { panic!("map failed") })
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(*mutex.try_lock().unwrap(), (1, 2));
+}
@@ -0,0 +1,129 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use tokio::task;
+use unsync::notify::Notify;
+
+#[cfg(not(miri))]
+const SIZE: u32 = 100_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+#[tokio::test]
+async fn test_notify_one() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let ping = Rc::new(Notify::new());
+    let pong = Rc::new(Notify::new());
+    let counter = Rc::new(Cell::new(0));
+
+    let a = local
+        .run_until(async move {
+            let a = task::spawn_local({
+                let ping = ping.clone();
+                let pong = pong.clone();
+                let counter = counter.clone();
+
+                async move {
+                    for n in 0..SIZE {
+                        ping.notified().await;
+                        counter.set(counter.get() + 1);
+                        pong.notify_one();
+
+                        if n % 3 == 0 {
+                            task::yield_now().await;
+                        }
+                    }
+                }
+            });
+
+            for n in 0..SIZE {
+                ping.notify_one();
+                pong.notified().await;
+                assert_eq!(counter.get(), n + 1);
+
+                if n % 5 == 0 {
+                    task::yield_now().await;
+                }
+            }
+
+            a.await
+        })
+        .await;
+
+    let () = a?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_notify_waiters() {
+    let local = task::LocalSet::new();
+
+    let notify = Rc::new(Notify::new());
+    let counter = Rc::new(Cell::new(0));
+
+    local
+        .run_until(async move {
+            let mut waiters = Vec::new();
+
+            for _ in 0..4 {
+                let notify = notify.clone();
+                let counter = counter.clone();
+
+                waiters.push(task::spawn_local(async move {
+                    notify.notified().await;
+                    counter.set(counter.get() + 1);
+                }));
+            }
+
+            task::yield_now().await;
+            notify.notify_waiters();
+
+            for waiter in waiters {
+                waiter.await.unwrap();
+            }
+
+            assert_eq!(counter.get(), 4);
+
+            // No permit is stored by `notify_waiters`.
+            notify.notify_waiters();
+            let waiter = notify.notified();
+            notify.notify_one();
+            waiter.await;
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_dropped_waiter_passes_notification() {
+    let local = task::LocalSet::new();
+
+    let notify = Rc::new(Notify::new());
+
+    local
+        .run_until(async move {
+            let a = task::spawn_local({
+                let notify = notify.clone();
+                async move { notify.notified().await }
+            });
+
+            task::yield_now().await;
+
+            let b = task::spawn_local({
+                let notify = notify.clone();
+                async move { notify.notified().await }
+            });
+
+            task::yield_now().await;
+
+            // The notification is meant for `a`, but it's cancelled before it
+            // gets a chance to observe it.
+            notify.notify_one();
+            a.abort();
+
+            assert!(a.await.unwrap_err().is_cancelled());
+            b.await.unwrap();
+        })
+        .await;
+}
@@ -0,0 +1,110 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use tokio::task;
+use unsync::notify::Notify;
+use unsync::once::{Lazy, OnceCell};
+
+#[tokio::test]
+async fn test_single_initializer() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let cell = Rc::new(OnceCell::new());
+    let calls = Rc::new(Cell::new(0));
+
+    local
+        .run_until(async {
+            let mut tasks = Vec::new();
+
+            for n in 0..16u32 {
+                let cell = cell.clone();
+                let calls = calls.clone();
+
+                tasks.push(task::spawn_local(async move {
+                    let value = cell
+                        .get_or_init(|| async move {
+                            calls.set(calls.get() + 1);
+
+                            for _ in 0..4 {
+                                task::yield_now().await;
+                            }
+
+                            n
+                        })
+                        .await;
+
+                    *value
+                }));
+            }
+
+            let mut values = Vec::new();
+
+            for t in tasks {
+                values.push(t.await?);
+            }
+
+            assert!(values.iter().all(|v| *v == values[0]));
+            Ok::<_, task::JoinError>(())
+        })
+        .await?;
+
+    assert_eq!(calls.get(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_after_dropped_initializer() {
+    let local = task::LocalSet::new();
+
+    let cell = Rc::new(OnceCell::new());
+    let never = Rc::new(Notify::new());
+
+    local
+        .run_until(async {
+            let stuck = task::spawn_local({
+                let cell = cell.clone();
+                let never = never.clone();
+
+                async move {
+                    *cell
+                        .get_or_init(|| async move {
+                            never.notified().await;
+                            1
+                        })
+                        .await
+                }
+            });
+
+            task::yield_now().await;
+
+            let waiter = task::spawn_local({
+                let cell = cell.clone();
+                async move { *cell.get_or_init(|| async { 2 }).await }
+            });
+
+            task::yield_now().await;
+            assert!(cell.get().is_none());
+
+            stuck.abort();
+            assert!(stuck.await.unwrap_err().is_cancelled());
+
+            assert_eq!(waiter.await.unwrap(), 2);
+            assert_eq!(cell.get(), Some(&2));
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_lazy() {
+    let calls = Cell::new(0);
+
+    let lazy = Lazy::new(|| async {
+        calls.set(calls.get() + 1);
+        task::yield_now().await;
+        42
+    });
+
+    let (a, b) = tokio::join!(lazy.force(), lazy.force());
+    assert_eq!((*a, *b), (42, 42));
+    assert_eq!(calls.get(), 1);
+}
@@ -63,3 +63,99 @@ async fn test_oneshot() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(actual, expected);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_poll_closed() -> Result<(), task::JoinError> {
+    use std::future::poll_fn;
+
+    let local = task::LocalSet::new();
+
+    let (mut tx, rx) = oneshot::channel::<u32>();
+
+    local
+        .run_until(async move {
+            let waiter = task::spawn_local(async move {
+                poll_fn(|cx| tx.poll_closed(cx)).await;
+                tx
+            });
+
+            task::yield_now().await;
+            drop(rx);
+
+            let tx = waiter.await?;
+            assert_eq!(tx.send(1), Err(oneshot::SendError(1)));
+            Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_recv_error() {
+    let (tx, rx) = oneshot::channel::<u32>();
+    drop(tx);
+    assert_eq!(rx.await, Err(oneshot::RecvError));
+
+    let (tx, rx) = oneshot::channel::<u32>();
+    drop(rx);
+    assert_eq!(tx.send(1).map_err(oneshot::SendError::into_inner), Err(1));
+}
+
+#[tokio::test]
+async fn test_try_recv_then_await() {
+    let (tx, mut rx) = oneshot::channel::<u32>();
+    assert_eq!(rx.try_recv(), Err(oneshot::TryRecvError::Empty));
+    assert_eq!(rx.try_recv(), Err(oneshot::TryRecvError::Empty));
+
+    let (value, ()) = tokio::join!(rx, async move {
+        assert!(tx.send(1).is_ok());
+    });
+
+    assert_eq!(value, Ok(1));
+
+    let (tx, mut rx) = oneshot::channel::<u32>();
+    drop(tx);
+    assert_eq!(rx.try_recv(), Err(oneshot::TryRecvError::Closed));
+}
+
+#[tokio::test]
+async fn test_closed_abandons_work() -> Result<(), task::JoinError> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, rx) = oneshot::channel::<u32>();
+
+    local
+        .run_until(async move {
+            let worker = task::spawn_local(async move {
+                let mut steps = 0;
+
+                // Keep computing until the requester is gone.
+                loop {
+                    tokio::select! {
+                        _ = tx.closed() => break,
+                        _ = task::yield_now() => steps += 1,
+                    }
+                }
+
+                assert!(tx.is_closed());
+                steps
+            });
+
+            for _ in 0..10 {
+                task::yield_now().await;
+            }
+
+            drop(rx);
+            assert!(worker.await? > 0);
+            Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_close() {
+    let (mut tx, mut rx) = oneshot::channel::<u32>();
+
+    let ((), ()) = tokio::join!(tx.closed(), async { rx.close() });
+    assert_eq!(tx.send(1), Err(oneshot::SendError(1)));
+    assert_eq!(rx.await, Err(oneshot::RecvError));
+}
@@ -0,0 +1,124 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tokio::task;
+use unsync::rwlock::{RwLock, RwLockWriteGuard};
+
+#[cfg(not(miri))]
+const SIZE: u32 = 10_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+#[tokio::test]
+async fn test_rwlock() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let lock = Rc::new(RwLock::new((0u32, 0u32)));
+
+    local
+        .run_until(async {
+            let mut tasks = Vec::new();
+
+            for _ in 0..4 {
+                let lock = lock.clone();
+
+                tasks.push(task::spawn_local(async move {
+                    for n in 0..SIZE {
+                        let mut guard = lock.write().await;
+                        guard.0 += 1;
+
+                        if n % 3 == 0 {
+                            task::yield_now().await;
+                        }
+
+                        guard.1 += 1;
+                    }
+                }));
+            }
+
+            for _ in 0..4 {
+                let lock = lock.clone();
+
+                tasks.push(task::spawn_local(async move {
+                    for n in 0..SIZE {
+                        let guard = lock.read().await;
+
+                        if n % 5 == 0 {
+                            task::yield_now().await;
+                        }
+
+                        // Writers never run while we're holding the lock.
+                        assert_eq!(guard.0, guard.1);
+                    }
+                }));
+            }
+
+            for t in tasks {
+                t.await?;
+            }
+
+            Ok::<_, task::JoinError>(())
+        })
+        .await?;
+
+    assert_eq!(*lock.read().await, (SIZE * 4, SIZE * 4));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_writer_preference() {
+    let local = task::LocalSet::new();
+
+    let lock = Rc::new(RwLock::new(()));
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    local
+        .run_until(async {
+            let reader = lock.read().await;
+
+            let writer = task::spawn_local({
+                let lock = lock.clone();
+                let order = order.clone();
+
+                async move {
+                    let guard = lock.write().await;
+                    order.borrow_mut().push("write");
+                    task::yield_now().await;
+
+                    // Readers waiting behind us get in once we downgrade.
+                    let _guard = RwLockWriteGuard::downgrade(guard);
+                    task::yield_now().await;
+                    order.borrow_mut().push("downgraded");
+                }
+            });
+
+            task::yield_now().await;
+
+            // A writer is waiting, so new readers have to wait too.
+            assert!(lock.try_read().is_err());
+
+            let late_reader = task::spawn_local({
+                let lock = lock.clone();
+                let order = order.clone();
+
+                async move {
+                    let _guard = lock.read().await;
+                    order.borrow_mut().push("read");
+                }
+            });
+
+            task::yield_now().await;
+            order.borrow_mut().push("release");
+            drop(reader);
+
+            writer.await.unwrap();
+            late_reader.await.unwrap();
+        })
+        .await;
+
+    assert_eq!(
+        *order.borrow(),
+        vec!["release", "write", "read", "downgraded"]
+    );
+}
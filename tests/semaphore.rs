@@ -0,0 +1,130 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use tokio::task;
+use unsync::semaphore::{AcquireError, Semaphore};
+
+#[cfg(not(miri))]
+const SIZE: u32 = 10_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+#[tokio::test]
+async fn test_semaphore() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let semaphore = Rc::new(Semaphore::new(3));
+    let active = Rc::new(Cell::new(0));
+
+    local
+        .run_until(async {
+            let mut tasks = Vec::new();
+
+            for t in 0..8 {
+                let semaphore = semaphore.clone();
+                let active = active.clone();
+
+                tasks.push(task::spawn_local(async move {
+                    for n in 0..SIZE {
+                        let permits = if (n + t) % 4 == 0 { 3 } else { 1 };
+                        let _permit = semaphore.clone().acquire_owned(permits).await.unwrap();
+
+                        active.set(active.get() + permits);
+                        assert!(active.get() <= 3);
+
+                        if n % 3 == 0 {
+                            task::yield_now().await;
+                        }
+
+                        active.set(active.get() - permits);
+                    }
+                }));
+            }
+
+            for t in tasks {
+                t.await?;
+            }
+
+            Ok::<_, task::JoinError>(())
+        })
+        .await?;
+
+    assert_eq!(semaphore.available_permits(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_large_acquire_not_starved() {
+    let local = task::LocalSet::new();
+
+    let semaphore = Rc::new(Semaphore::new(2));
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    local
+        .run_until(async {
+            let permit = semaphore.acquire(1).await.unwrap();
+
+            let large = task::spawn_local({
+                let semaphore = semaphore.clone();
+                let order = order.clone();
+
+                async move {
+                    let _permit = semaphore.acquire(2).await.unwrap();
+                    order.borrow_mut().push("large");
+                }
+            });
+
+            task::yield_now().await;
+
+            // One permit is available, but the large acquire is ahead in line.
+            assert!(semaphore.try_acquire(1).is_err());
+
+            let small = task::spawn_local({
+                let semaphore = semaphore.clone();
+                let order = order.clone();
+
+                async move {
+                    let _permit = semaphore.acquire(1).await.unwrap();
+                    order.borrow_mut().push("small");
+                }
+            });
+
+            task::yield_now().await;
+            drop(permit);
+
+            large.await.unwrap();
+            small.await.unwrap();
+        })
+        .await;
+
+    assert_eq!(*order.borrow(), vec!["large", "small"]);
+}
+
+#[tokio::test]
+async fn test_close() {
+    let local = task::LocalSet::new();
+
+    let semaphore = Rc::new(Semaphore::new(0));
+
+    local
+        .run_until(async {
+            let waiter = task::spawn_local({
+                let semaphore = semaphore.clone();
+                async move { semaphore.acquire(1).await.map(|p| p.num_permits()) }
+            });
+
+            task::yield_now().await;
+            semaphore.close();
+
+            assert_eq!(waiter.await.unwrap(), Err(AcquireError));
+        })
+        .await;
+}
+
+#[test]
+#[should_panic = "would overflow"]
+fn test_add_permits_overflow() {
+    let semaphore = Semaphore::new(1);
+    semaphore.add_permits(usize::MAX);
+}
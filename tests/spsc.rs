@@ -58,7 +58,7 @@ async fn test_try_send() -> Result<(), task::JoinError> {
     assert!(tx.try_send(1).is_ok());
     assert!(tx.try_send(2).is_ok());
     assert!(tx.try_send(3).is_ok());
-    assert!(tx.try_send(4).is_err());
+    assert_eq!(tx.try_send(4), Err(spsc::TrySendError::Full(4)));
 
     let first = rx.recv().await;
     assert_eq!(first, Some(1));
@@ -74,5 +74,340 @@ async fn test_try_send() -> Result<(), task::JoinError> {
     }
 
     assert_eq!(collected, vec![2, 3, 5]);
+
+    let (mut tx, rx) = spsc::channel(1);
+    drop(rx);
+    let error = tx.try_send(7).unwrap_err();
+    assert_eq!(error, spsc::TrySendError::Closed(7));
+    assert_eq!(error.into_inner(), 7);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_poll_methods() -> Result<(), Box<dyn std::error::Error>> {
+    use std::future::poll_fn;
+
+    let local = task::LocalSet::new();
+
+    let (mut tx, mut rx) = spsc::channel(2);
+
+    let (a, b) = local
+        .run_until(async move {
+            let a = task::spawn_local(async move {
+                let mut out = Vec::new();
+
+                while let Some(value) = poll_fn(|cx| rx.poll_recv(cx)).await {
+                    out.push(value);
+                }
+
+                out
+            });
+
+            let b = task::spawn_local(async move {
+                for n in 0..SIZE {
+                    poll_fn(|cx| tx.poll_ready(cx)).await?;
+                    tx.start_send(n)?;
+                }
+
+                Ok::<_, Box<dyn std::error::Error>>(())
+            });
+
+            tokio::join!(a, b)
+        })
+        .await;
+
+    b??;
+    assert_eq!(a?, (0..SIZE).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nameable_futures() {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// A hand-written future which stores an in-flight receive.
+    struct FirstTwo<'a> {
+        recv: spsc::RecvFut<'a, u32>,
+        first: Option<u32>,
+    }
+
+    impl Future for FirstTwo<'_> {
+        type Output = Option<(u32, u32)>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let value = match Pin::new(&mut self.recv).poll(cx) {
+                Poll::Ready(Some(value)) => value,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match self.first.take() {
+                Some(first) => Poll::Ready(Some((first, value))),
+                None => {
+                    self.first = Some(value);
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    let (mut tx, mut rx) = spsc::channel(2);
+    assert!(tx.try_send(1).is_ok());
+    assert!(tx.try_send(2).is_ok());
+
+    let send = tx.send(3);
+    assert!(format!("{:?}", send).starts_with("SendFut"));
+    drop(send);
+
+    let recv = rx.recv();
+    assert!(format!("{:?}", recv).starts_with("RecvFut"));
+
+    let first_two = FirstTwo { recv, first: None };
+    assert_eq!(first_two.await, Some((1, 2)));
+}
+
+#[tokio::test]
+async fn test_reserve() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, mut rx) = spsc::channel(4);
+
+    let received = local
+        .run_until(async move {
+            let receiver = task::spawn_local(async move {
+                let mut received = Vec::new();
+
+                while let Some(value) = rx.recv().await {
+                    received.push(value);
+                }
+
+                received
+            });
+
+            for n in 0..SIZE / 4 {
+                let permits = tx.reserve_many(4).await?;
+
+                for (i, permit) in (0..4).zip(permits) {
+                    permit.send(n * 4 + i);
+                }
+            }
+
+            // Reserving fails once the receiver is gone.
+            drop(tx);
+            let received = receiver.await?;
+
+            let (mut tx, rx) = spsc::channel::<u32>(1);
+            drop(rx);
+            assert!(tx.reserve().await.is_err());
+
+            Ok::<_, Box<dyn std::error::Error>>(received)
+        })
+        .await?;
+
+    assert_eq!(received, (0..SIZE / 4 * 4).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_zero_sized_capacity() {
+    let (mut tx, mut rx) = spsc::channel::<()>(2);
+
+    assert!(tx.try_send(()).is_ok());
+    assert!(tx.try_send(()).is_ok());
+    assert!(tx.try_send(()).is_err());
+
+    assert_eq!(rx.recv().await, Some(()));
+    tx.reserve().await.unwrap().send(());
+    assert!(tx.try_send(()).is_err());
+}
+
+#[tokio::test]
+#[should_panic = "cannot reserve more than the capacity of the channel"]
+async fn test_zero_sized_reserve_many() {
+    let (mut tx, _rx) = spsc::channel::<()>(2);
+    let _ = tx.reserve_many(3).await;
+}
+
+#[tokio::test]
+async fn test_try_recv_drain() {
+    let (mut tx, mut rx) = spsc::channel(4);
+
+    for round in 0..4 {
+        for n in 0..4 {
+            assert!(tx.try_send(round * 4 + n).is_ok());
+        }
+
+        // Drain everything that is buffered without suspending.
+        let drained = std::iter::from_fn(|| rx.try_recv().ok()).collect::<Vec<_>>();
+        assert_eq!(drained, (round * 4..round * 4 + 4).collect::<Vec<_>>());
+        assert_eq!(rx.try_recv(), Err(spsc::TryRecvError::Empty));
+    }
+
+    drop(tx);
+    assert_eq!(rx.try_recv(), Err(spsc::TryRecvError::Closed));
+}
+
+#[tokio::test]
+async fn test_closed() -> Result<(), task::JoinError> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, mut rx) = spsc::channel::<u32>(1);
+
+    local
+        .run_until(async move {
+            let sender = task::spawn_local(async move {
+                assert!(tx.try_send(1).is_ok());
+                tx.closed().await;
+                assert!(tx.is_closed());
+                assert_eq!(tx.try_send(2), Err(spsc::TrySendError::Closed(2)));
+            });
+
+            assert_eq!(rx.recv().await, Some(1));
+            task::yield_now().await;
+            drop(rx);
+
+            sender.await
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_close_drains_buffer() -> Result<(), task::JoinError> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, mut rx) = spsc::channel::<u32>(2);
+
+    local
+        .run_until(async move {
+            let sender = task::spawn_local(async move {
+                let mut sent = 0;
+
+                while tx.send(sent).await.is_ok() {
+                    sent += 1;
+                }
+
+                sent
+            });
+
+            assert_eq!(rx.recv().await, Some(0));
+            rx.close();
+
+            // The blocked sender is woken up and fails, while everything that
+            // was accepted can still be received.
+            let sent = sender.await?;
+            let mut received = vec![0];
+
+            while let Some(value) = rx.recv().await {
+                received.push(value);
+            }
+
+            assert_eq!(received, (0..sent).collect::<Vec<_>>());
+            Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_reroute_undelivered() -> Result<(), task::JoinError> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, mut rx) = spsc::channel::<u32>(8);
+
+    local
+        .run_until(async move {
+            // A consumer which dies after handling a couple of messages.
+            let consumer = task::spawn_local(async move {
+                let mut handled = Vec::new();
+
+                while let Some(value) = rx.recv().await {
+                    handled.push(value);
+
+                    if handled.len() == 2 {
+                        break;
+                    }
+                }
+
+                handled
+            });
+
+            for n in 0..6 {
+                assert!(tx.try_send(n).is_ok());
+            }
+
+            let handled = consumer.await?;
+            assert!(tx.is_closed());
+
+            let mut all = handled;
+            all.extend(tx.take_undelivered());
+            assert_eq!(all, (0..6).collect::<Vec<_>>());
+            Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_batched() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, mut rx) = spsc::channel::<u32>(16);
+
+    let received = local
+        .run_until(async move {
+            let receiver = task::spawn_local(async move {
+                let mut received = Vec::new();
+
+                while rx.recv_many(&mut received, 7).await > 0 {}
+
+                received
+            });
+
+            tx.send_all(0..SIZE).await?;
+            drop(tx);
+
+            Ok::<_, Box<dyn std::error::Error>>(receiver.await?)
+        })
+        .await?;
+
+    assert_eq!(received, (0..SIZE).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_slice() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let (mut tx, mut rx) = spsc::channel::<u32>(5);
+    let values = (0..SIZE).collect::<Vec<_>>();
+    let expected = values.clone();
+
+    let received = local
+        .run_until(async move {
+            let receiver = task::spawn_local(async move {
+                let mut received = Vec::new();
+                // Smaller than the channel, so reads wrap around the ring.
+                let mut buf = [0; 3];
+
+                loop {
+                    let n = rx.recv_into(&mut buf).await;
+
+                    if n == 0 {
+                        break received;
+                    }
+
+                    received.extend_from_slice(&buf[..n]);
+                }
+            });
+
+            tx.send_slice(&values).await.map_err(|e| e.to_string())?;
+            drop(tx);
+
+            Ok::<_, Box<dyn std::error::Error>>(receiver.await?)
+        })
+        .await?;
+
+    assert_eq!(received, expected);
     Ok(())
 }
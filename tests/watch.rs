@@ -0,0 +1,91 @@
+use tokio::task;
+use unsync::watch;
+
+#[cfg(not(miri))]
+const SIZE: u32 = 100_000;
+
+#[cfg(miri)]
+const SIZE: u32 = 10;
+
+#[tokio::test]
+async fn test_watch() -> Result<(), Box<dyn std::error::Error>> {
+    let local = task::LocalSet::new();
+
+    let (tx, rx) = watch::channel(0);
+
+    let (receivers, b) = local
+        .run_until(async move {
+            let mut receivers = Vec::new();
+
+            for _ in 0..16 {
+                let mut rx = rx.clone();
+
+                receivers.push(task::spawn_local(async move {
+                    let mut seen = Vec::new();
+
+                    while rx.changed().await.is_ok() {
+                        let value = *rx.borrow();
+                        seen.push(value);
+
+                        if value % 3 == 0 {
+                            task::yield_now().await;
+                        }
+                    }
+
+                    seen
+                }));
+            }
+
+            drop(rx);
+
+            let b = task::spawn_local(async move {
+                for n in 1..=SIZE {
+                    let _ = tx.send(n);
+
+                    if n % 5 == 0 {
+                        task::yield_now().await;
+                    }
+                }
+            });
+
+            let mut seen = Vec::new();
+
+            for receiver in receivers {
+                seen.push(receiver.await);
+            }
+
+            (seen, b.await)
+        })
+        .await;
+
+    let () = b?;
+
+    for seen in receivers {
+        let seen = seen?;
+        // Values are only ever observed in increasing order, and the last
+        // value is always observed.
+        assert!(seen.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(seen.last(), Some(&SIZE));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_versions() {
+    let (tx, mut rx) = watch::channel(1);
+    let mut rx2 = rx.clone();
+
+    tx.send_modify(|v| *v += 1);
+
+    assert!(rx.changed().await.is_ok());
+    assert_eq!(*rx.borrow(), 2);
+
+    // A receiver which already observed the change has nothing new to see once
+    // the sender is gone, but others still observe the last change.
+    drop(tx);
+    assert_eq!(rx.changed().await, Err(watch::RecvError));
+    assert!(rx2.changed().await.is_ok());
+    assert_eq!(*rx2.borrow(), 2);
+    assert_eq!(rx2.changed().await, Err(watch::RecvError));
+}